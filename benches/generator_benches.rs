@@ -1,6 +1,11 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use sudoku::generator::{
-    random_sudoku_puzzle_easy, random_sudoku_puzzle_extraeasy, random_sudoku_puzzle_extrahard, random_sudoku_puzzle_hard, random_sudoku_puzzle_normal
+use rand::{rngs::StdRng, SeedableRng};
+use sudoku::{
+    generator::{
+        random_sudoku_puzzle_easy, random_sudoku_puzzle_extraeasy, random_sudoku_puzzle_extrahard,
+        random_sudoku_puzzle_hard, random_sudoku_puzzle_normal, random_sudoku_puzzle_with_rng,
+    },
+    solver::{advanced::AdvancedSolver, bitboard::BitboardSolver, stochastic::StochasticSolver},
 };
 
 fn benchmarks(c: &mut Criterion) {
@@ -29,6 +34,28 @@ fn benchmarks(c: &mut Criterion) {
             random_sudoku_puzzle_extrahard();
         })
     });
+
+    // 同一个种子挖出的题目相同，两者只差在判断唯一解的求解器上
+    c.bench_function("unique by AdvancedSolver", |b| {
+        b.iter(|| {
+            random_sudoku_puzzle_with_rng::<StochasticSolver, AdvancedSolver, f32>(
+                55,
+                0.0,
+                f32::MAX,
+                &mut StdRng::seed_from_u64(42),
+            );
+        })
+    });
+    c.bench_function("unique by BitboardSolver", |b| {
+        b.iter(|| {
+            random_sudoku_puzzle_with_rng::<StochasticSolver, BitboardSolver, f32>(
+                55,
+                0.0,
+                f32::MAX,
+                &mut StdRng::seed_from_u64(42),
+            );
+        })
+    });
 }
 
 criterion_group!(benches, benchmarks);
@@ -1,7 +1,10 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use sudoku::{
     generator::random_sudoku_puzzle_normal,
-    solver::{advanced::AdvancedSolver, stochastic::StochasticSolver, Solver},
+    solver::{
+        advanced::AdvancedSolver, bitboard::BitboardSolver, dlx::DlxSolver,
+        stochastic::StochasticSolver, Solver,
+    },
     state::{full_state::FullState, simple_state::SimpleState},
 };
 
@@ -19,6 +22,18 @@ fn benchmarks(c: &mut Criterion) {
             solver.any_solution();
         })
     });
+    let mut solver = DlxSolver::from(puzzle);
+    c.bench_function("DlxSolver", |b| {
+        b.iter(|| {
+            solver.any_solution();
+        })
+    });
+    let mut solver = BitboardSolver::from(puzzle);
+    c.bench_function("BitboardSolver", |b| {
+        b.iter(|| {
+            solver.any_solution();
+        })
+    });
 }
 
 criterion_group!(benches, benchmarks);
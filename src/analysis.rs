@@ -0,0 +1,317 @@
+use std::{fmt::Display, fs, io, path::Path, str::FromStr, time::Duration};
+
+use itertools::Itertools;
+
+use crate::{
+    solver::{
+        advanced::{AdvancedSolver, SolverConfig, StepPolicy},
+        bitboard::BitboardSolver,
+        Solver,
+    },
+    state::{full_state::FullState, Fillable, State},
+    techniques::TechniqueId,
+    Grid,
+};
+
+const FEATURE_CNT: usize = 4 + TechniqueId::DEFAULT.len();
+
+/// The name of every feature used by [`SolveTimeModel`], in the order of [`features`].
+pub fn feature_names() -> Vec<String> {
+    ["intercept", "clue_cnt", "guesses", "branching"]
+        .iter()
+        .map(|name| name.to_string())
+        .chain(TechniqueId::DEFAULT.iter().map(|id| format!("{:?}", id)))
+        .collect()
+}
+
+/// Solve `puzzle` logically, always taking the easiest step, and describe the solve:
+/// a constant 1, the clue count, the guesses, the mean count of different steps available
+/// at each step, and how many times each of [`TechniqueId::DEFAULT`] was applied.
+///
+/// Return `None` if the puzzle has no solution.
+pub fn features(puzzle: &Grid) -> Option<Vec<f64>> {
+    let mut solver = AdvancedSolver::<FullState>::from(*puzzle).with_config(
+        SolverConfig::default()
+            .policy(StepPolicy::Easiest)
+            .record_path(true),
+    );
+    solver.any_solution()?;
+    let report = solver.report()?;
+    let mut features = vec![
+        1.0,
+        report.clue_cnt as f64,
+        report.guesses as f64,
+        report.branching.iter().sum::<usize>() as f64 / report.branching.len().max(1) as f64,
+    ];
+    features.extend(TechniqueId::DEFAULT.iter().map(|id| {
+        solver
+            .path()
+            .iter()
+            .filter(|step| step.technique == Some(*id))
+            .count() as f64
+    }));
+    Some(features)
+}
+
+/// A linear model predicting how long a human takes to solve a puzzle from its [`features`].
+///
+/// The model can be saved to and loaded from a text file with one `name weight` pair per line.
+///
+/// ```
+/// use sudoku::{analysis::SolveTimeModel, generator::random_sudoku_puzzle_easy};
+///
+/// let model = SolveTimeModel::default();
+/// let text = model.to_string();
+/// assert_eq!(text.parse::<SolveTimeModel>().unwrap(), model);
+/// println!("{:?}", model.predict(&random_sudoku_puzzle_easy()));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolveTimeModel {
+    weights: Vec<f64>,
+}
+
+impl Default for SolveTimeModel {
+    // 凭经验给出的系数，单位为秒，有数据后应该用 fit 重新拟合
+    fn default() -> Self {
+        let mut weights = vec![60.0, -0.5, 120.0, -5.0];
+        weights.extend(TechniqueId::DEFAULT.iter().map(|id| match id {
+            TechniqueId::HiddenSingle => 3.0,
+            TechniqueId::NakedSingle => 6.0,
+            TechniqueId::Pointing | TechniqueId::Claiming => 15.0,
+            TechniqueId::NakedPair => 25.0,
+            TechniqueId::HiddenPair => 30.0,
+            TechniqueId::XWing => 40.0,
+            TechniqueId::NakedSubset => 45.0,
+            TechniqueId::Swordfish => 60.0,
+            _ => 90.0,
+        }));
+        Self { weights }
+    }
+}
+
+impl SolveTimeModel {
+    /// Fit the weights to `samples` of puzzles and the seconds players took to solve them,
+    /// by least squares with a tiny ridge penalty to cope with features which never vary.
+    /// Puzzles without any solution are skipped.
+    #[allow(clippy::needless_range_loop)]
+    pub fn fit(samples: &[(Grid, f64)]) -> Self {
+        // 正规方程 (XᵀX + λI) w = Xᵀy
+        let mut a = vec![vec![0.0; FEATURE_CNT + 1]; FEATURE_CNT];
+        for (puzzle, seconds) in samples {
+            let Some(x) = features(puzzle) else {
+                continue;
+            };
+            for i in 0..FEATURE_CNT {
+                for j in 0..FEATURE_CNT {
+                    a[i][j] += x[i] * x[j];
+                }
+                a[i][FEATURE_CNT] += x[i] * seconds;
+            }
+        }
+        for i in 1..FEATURE_CNT {
+            a[i][i] += 1e-6;
+        }
+
+        // 列主元高斯消元
+        for col in 0..FEATURE_CNT {
+            let pivot = (col..FEATURE_CNT)
+                .max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs()))
+                .unwrap();
+            a.swap(col, pivot);
+            if a[col][col].abs() < 1e-12 {
+                continue;
+            }
+            for row in 0..FEATURE_CNT {
+                if row != col {
+                    let factor = a[row][col] / a[col][col];
+                    for k in col..=FEATURE_CNT {
+                        a[row][k] -= factor * a[col][k];
+                    }
+                }
+            }
+        }
+        let weights = (0..FEATURE_CNT)
+            .map(|i| {
+                if a[i][i].abs() < 1e-12 {
+                    0.0
+                } else {
+                    a[i][FEATURE_CNT] / a[i][i]
+                }
+            })
+            .collect();
+        Self { weights }
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Predict the time to solve `puzzle`, or `None` if it has no solution.
+    /// Predictions too long for a `Duration` are clamped to `Duration::MAX`.
+    pub fn predict(&self, puzzle: &Grid) -> Option<Duration> {
+        let x = features(puzzle)?;
+        let seconds: f64 = x.iter().zip(&self.weights).map(|(x, w)| x * w).sum();
+        Some(Duration::try_from_secs_f64(seconds.max(0.0)).unwrap_or(Duration::MAX))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl Display for SolveTimeModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, weight) in feature_names().iter().zip(&self.weights) {
+            writeln!(f, "{} {}", name, weight)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SolveTimeModel {
+    type Err = String;
+
+    /// Features missing from `s` get a weight of 0. Weights must be finite numbers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let names = feature_names();
+        let mut weights = vec![0.0; FEATURE_CNT];
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (name, weight) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("expected `name weight`, found `{}`", line))?;
+            let i = names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| format!("unknown feature `{}`", name))?;
+            weights[i] = weight
+                .trim()
+                .parse()
+                .ok()
+                .filter(|weight: &f64| weight.is_finite())
+                .ok_or_else(|| format!("invalid weight of `{}`: `{}`", name, weight.trim()))?;
+        }
+        Ok(Self { weights })
+    }
+}
+
+/// Predict the time to solve `puzzle` with the default [`SolveTimeModel`].
+/// Puzzles without a solution get `Duration::ZERO`;
+/// use [`SolveTimeModel::predict`] to tell them apart.
+pub fn predict_solve_time(puzzle: &Grid) -> Duration {
+    SolveTimeModel::default()
+        .predict(puzzle)
+        .unwrap_or(Duration::ZERO)
+}
+
+// 反复应用第一个能用的技巧，直到无法推进
+fn apply_until_stuck(state: &mut FullState, techniques: &[TechniqueId]) {
+    while let Some(deduction) = techniques.iter().find_map(|id| id.analyze(state)) {
+        deduction.step.apply(state);
+    }
+}
+
+fn is_full(state: &FullState) -> bool {
+    state.grid().0.iter().flatten().all(|v| *v > 0)
+}
+
+/// Return whether `puzzle` can be solved without guessing, using only `techniques`.
+pub fn solves_with(puzzle: &Grid, techniques: &[TechniqueId]) -> bool {
+    let mut state = FullState::from(*puzzle);
+    apply_until_stuck(&mut state, techniques);
+    // 题目本身可能违反规则，填满后还要与解比较
+    is_full(&state) && BitboardSolver::from(*puzzle).any_solution() == Some(state.grid())
+}
+
+/// Find a smallest set of cells which, once revealed from the solution,
+/// lets the rest of `puzzle` be solved with **Hidden Single** and **Naked Single** only.
+/// Return the size of the set and the cells in it, or `None` if the puzzle
+/// does not have a unique solution.
+///
+/// The search tries every set of each size in turn, so it gets slow
+/// for the rare puzzles whose backdoor has more than 3 cells.
+pub fn backdoor_size(puzzle: &Grid) -> Option<(usize, Vec<(usize, usize)>)> {
+    const SINGLES: [TechniqueId; 2] = [TechniqueId::HiddenSingle, TechniqueId::NakedSingle];
+
+    let mut solver = BitboardSolver::from(*puzzle);
+    if !solver.have_unique_solution() {
+        return None;
+    }
+    let solution = solver.any_solution()?;
+
+    // 单数能填上的格子无需揭示，只在卡住后仍为空的格子中搜索
+    let mut stuck = FullState::from(*puzzle);
+    apply_until_stuck(&mut stuck, &SINGLES);
+    let blanks: Vec<(usize, usize)> = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|(r, c)| stuck.is_cell_empty(*r, *c))
+        .collect();
+    (0..=blanks.len()).find_map(|size| {
+        blanks
+            .iter()
+            .copied()
+            .combinations(size)
+            .find(|cells| {
+                let mut state = stuck.clone();
+                for (r, c) in cells {
+                    if state.is_cell_empty(*r, *c) {
+                        state.fill_cell(*r, *c, solution.0[*r][*c]);
+                    }
+                }
+                apply_until_stuck(&mut state, &SINGLES);
+                is_full(&state)
+            })
+            .map(|cells| (size, cells))
+    })
+}
+
+fn clues(puzzle: &Grid) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|(r, c)| puzzle.0[*r][*c] != 0)
+}
+
+fn unique_without(puzzle: &Grid, r: usize, c: usize) -> bool {
+    let mut fewer = *puzzle;
+    fewer.0[r][c] = 0;
+    BitboardSolver::from(fewer).have_unique_solution()
+}
+
+/// Return every clue of `puzzle` whose removal alone keeps the solution unique.
+/// A puzzle without a unique solution has no redundant clue.
+pub fn redundant_clues(puzzle: &Grid) -> Vec<(usize, usize)> {
+    if !BitboardSolver::from(*puzzle).have_unique_solution() {
+        return vec![];
+    }
+    clues(puzzle)
+        .filter(|(r, c)| unique_without(puzzle, *r, *c))
+        .collect()
+}
+
+/// Return whether `puzzle` has a unique solution and loses it when any clue is removed.
+pub fn is_minimal(puzzle: &Grid) -> bool {
+    BitboardSolver::from(*puzzle).have_unique_solution()
+        && clues(puzzle).all(|(r, c)| !unique_without(puzzle, r, c))
+}
+
+/// Remove clues from `puzzle`, in row-major order, as long as the solution stays unique.
+/// The result is minimal, though usually harder than `puzzle`.
+/// A puzzle without a unique solution is returned unchanged.
+pub fn make_minimal(puzzle: &Grid) -> Grid {
+    let mut minimal = *puzzle;
+    if !BitboardSolver::from(minimal).have_unique_solution() {
+        return minimal;
+    }
+    // 去掉线索只会让其余线索更加必要，所以一遍就够了
+    for (r, c) in clues(puzzle) {
+        if unique_without(&minimal, r, c) {
+            minimal.0[r][c] = 0;
+        }
+    }
+    minimal
+}
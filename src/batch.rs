@@ -0,0 +1,169 @@
+use std::{fmt::Display, num::NonZeroUsize};
+
+use crate::{
+    judge::{validate, PuzzleError},
+    solver::{Grader, SolutionCount, Solver},
+    Grid,
+};
+
+/// How a batch is spread over threads.
+#[derive(Clone, Debug, Default)]
+pub struct BatchConfig {
+    threads: Option<NonZeroUsize>,
+}
+
+impl BatchConfig {
+    /// Use `threads` worker threads. By default, as many as the available parallelism,
+    /// or the global pool with the `rayon` feature.
+    pub fn threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    fn thread_cnt(&self) -> usize {
+        self.threads
+            .or_else(|| std::thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+    }
+}
+
+/// The result of solving and rating one puzzle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rated<T> {
+    pub solution: Grid,
+    pub unique: bool,
+    pub difficulty: T,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchError {
+    Invalid(PuzzleError),
+    NoSolution,
+}
+
+impl Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchError::Invalid(err) => write!(f, "invalid puzzle: {}", err),
+            BatchError::NoSolution => write!(f, "the puzzle has no solution"),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+impl From<PuzzleError> for BatchError {
+    fn from(err: PuzzleError) -> Self {
+        Self::Invalid(err)
+    }
+}
+
+/// Validate, solve, check the uniqueness of and rate `puzzle` with the solver `S`.
+pub fn rate<S, T>(puzzle: Grid) -> Result<Rated<T>, BatchError>
+where
+    S: Solver + Grader<T> + From<Grid>,
+    T: PartialOrd,
+{
+    validate(&puzzle)?;
+    // 数到第二个解就停下，同一次搜索留下的解和难度即为结果
+    let mut solver = S::from(puzzle);
+    let solution_cnt = solver.count_solutions(2);
+    if solution_cnt == SolutionCount::Exact(0) {
+        return Err(BatchError::NoSolution);
+    }
+    let difficulty = solver.difficulty();
+    let solution = match solver.last_solution() {
+        Some(solution) => solution,
+        None => solver.any_solution().ok_or(BatchError::NoSolution)?,
+    };
+    Ok(Rated {
+        solution,
+        unique: solution_cnt == SolutionCount::Exact(1),
+        difficulty,
+    })
+}
+
+/// Run [`rate`] on every puzzle in parallel. The results are in the order of `puzzles`.
+///
+/// ```
+/// use sudoku::{
+///     batch::{rate_batch, BatchConfig},
+///     generator::random_sudoku_puzzle_ultimate,
+///     solver::advanced::AdvancedSolver,
+/// };
+///
+/// let puzzles: Vec<_> = (0..4).map(|_| random_sudoku_puzzle_ultimate()).collect();
+/// let results = rate_batch::<AdvancedSolver, f32>(puzzles, &BatchConfig::default());
+/// assert!(results.iter().all(|result| result.as_ref().unwrap().unique));
+/// ```
+pub fn rate_batch<S, T>(
+    puzzles: impl IntoIterator<Item = Grid>,
+    config: &BatchConfig,
+) -> Vec<Result<Rated<T>, BatchError>>
+where
+    S: Solver + Grader<T> + From<Grid>,
+    T: PartialOrd + Send,
+{
+    let puzzles: Vec<Grid> = puzzles.into_iter().collect();
+    run_parallel(&puzzles, config, rate::<S, T>)
+}
+
+#[cfg(not(feature = "rayon"))]
+fn run_parallel<R, F>(puzzles: &[Grid], config: &BatchConfig, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(Grid) -> R + Sync,
+{
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    let thread_cnt = config.thread_cnt();
+    // 各线程依次领取下一道题，结果按下标放回原位
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<R>> = (0..puzzles.len()).map(|_| None).collect();
+    thread::scope(|s| {
+        let workers: Vec<_> = (0..thread_cnt.min(puzzles.len()))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= puzzles.len() {
+                            return done;
+                        }
+                        done.push((i, f(puzzles[i])));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            for (i, result) in worker.join().unwrap() {
+                results[i] = Some(result);
+            }
+        }
+    });
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+#[cfg(feature = "rayon")]
+fn run_parallel<R, F>(puzzles: &[Grid], config: &BatchConfig, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(Grid) -> R + Sync,
+{
+    use rayon::prelude::*;
+
+    let run = || puzzles.par_iter().map(|puzzle| f(*puzzle)).collect();
+    // 只有指定了线程数才另建线程池，建不成时退回全局线程池
+    match config.threads.map(|threads| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.get())
+            .build()
+    }) {
+        Some(Ok(pool)) => pool.install(run),
+        _ => run(),
+    }
+}
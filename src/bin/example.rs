@@ -10,7 +10,7 @@ use sudoku::{
         singles::{
             HiddenSingle, HiddenSingleBlock, HiddenSingleColumn, HiddenSingleRow, NakedSingle,
         },
-        Direct, ReducingCandidates,
+        Technique,
     },
 };
 
@@ -22,7 +22,7 @@ fn main() {
 
     let state = FullState::from(grid);
 
-    let direct_techniques: [(&mut dyn Direct<FullState>, &str); 5] = [
+    let direct_techniques: [(&mut dyn Technique<FullState>, &str); 5] = [
         (&mut HiddenSingle::default(), "HiddenSingle"),
         (&mut HiddenSingleBlock::default(), "HiddenSingleBlock"),
         (&mut HiddenSingleRow::default(), "HiddenSingleRow"),
@@ -34,12 +34,12 @@ fn main() {
     for (technique, label) in direct_techniques {
         technique.analyze(&state);
         if technique.appliable() {
-            println!("{} - {}", label, technique.option().unwrap());
+            println!("{} - {}", label, technique.step().unwrap());
         }
     }
     println!();
 
-    let reducing_techniques: [(&mut dyn ReducingCandidates<FullState>, &str); 14] = [
+    let reducing_techniques: [(&mut dyn Technique<FullState>, &str); 14] = [
         (&mut Pointing::default(), "Pointing"),
         (&mut Claiming::default(), "Claiming"),
         (&mut NakedPair::default(), "NakedPair"),
@@ -60,7 +60,7 @@ fn main() {
     for (technique, label) in reducing_techniques {
         technique.analyze(&state);
         if technique.appliable() {
-            println!("{} - {}", label, technique.option().unwrap());
+            println!("{} - {}", label, technique.step().unwrap());
         }
     }
     println!();
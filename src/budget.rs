@@ -0,0 +1,100 @@
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A flag shared between threads to stop a running search.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Why a search stopped before finishing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interrupted {
+    Timeout,
+    Cancelled,
+}
+
+impl Display for Interrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Interrupted::Timeout => write!(f, "the time budget ran out"),
+            Interrupted::Cancelled => write!(f, "the search was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+/// How long a search may run. The default budget is unlimited.
+///
+/// ```
+/// use std::time::Duration;
+/// use sudoku::budget::{Budget, CancellationToken};
+///
+/// let token = CancellationToken::new();
+/// let budget = Budget::default()
+///     .timeout(Duration::from_millis(100))
+///     .token(token.clone());
+/// assert!(budget.check().is_ok());
+/// token.cancel();
+/// assert!(budget.check().is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    token: Option<CancellationToken>,
+}
+
+impl Budget {
+    /// Stop at `deadline`.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stop after `timeout` from now.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Stop once `token` is cancelled.
+    pub fn token(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn check(&self) -> Result<(), Interrupted> {
+        if self
+            .token
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+        {
+            return Err(Interrupted::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Interrupted::Timeout);
+        }
+        Ok(())
+    }
+}
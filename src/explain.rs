@@ -0,0 +1,279 @@
+use std::fmt::Display;
+
+use crate::techniques::{House, TechniqueInfo};
+
+/// The sentences used to explain techniques.
+/// Implement it to ship explanations in another language.
+///
+/// Every argument passed as `&str` has already been formatted
+/// by `house`, `cell` and `list` of the same catalog.
+pub trait MessageCatalog {
+    fn house(&self, house: House) -> String;
+    fn cell(&self, (r, c): (usize, usize)) -> String {
+        format!("R{}C{}", r + 1, c + 1)
+    }
+    fn list(&self, items: &[String]) -> String;
+    fn fish_name(&self, size: usize) -> String;
+
+    // 删去候选数 nums 的格子 cells，用于描述需要删去多组候选数的技巧
+    fn removal(&self, nums: &str, cells: &str) -> String;
+    fn removals(&self, removals: &[String]) -> String {
+        removals.join("; ")
+    }
+
+    fn hidden_single(&self, house: &str, num: i8, cell: &str) -> String;
+    fn naked_single(&self, cell: &str, num: i8) -> String;
+    fn pointing(&self, block: &str, num: i8, line: &str, rem_cells: &str) -> String;
+    fn claiming(&self, line: &str, num: i8, block: &str, rem_cells: &str) -> String;
+    fn naked_subset(&self, house: &str, cells: &str, nums: &str, removals: &str) -> String;
+    fn hidden_pair(&self, house: &str, nums: &str, cells: &str, removals: &str) -> String;
+    fn fish(&self, name: &str, num: i8, base: &str, cover: &str, rem_cells: &str) -> String;
+}
+
+pub struct English;
+
+impl MessageCatalog for English {
+    fn house(&self, house: House) -> String {
+        match house {
+            House::Row(r) => format!("row {}", r + 1),
+            House::Column(c) => format!("column {}", c + 1),
+            House::Block(b) => format!("block {}", b + 1),
+        }
+    }
+    fn list(&self, items: &[String]) -> String {
+        match items {
+            [] => String::new(),
+            [item] => item.clone(),
+            [init @ .., last] => format!("{} and {}", init.join(", "), last),
+        }
+    }
+    fn fish_name(&self, size: usize) -> String {
+        match size {
+            2 => "an X-Wing",
+            3 => "a Swordfish",
+            4 => "a Jellyfish",
+            _ => "a fish",
+        }
+        .to_string()
+    }
+    fn removal(&self, nums: &str, cells: &str) -> String {
+        format!("remove {} from {}", nums, cells)
+    }
+    fn hidden_single(&self, house: &str, num: i8, cell: &str) -> String {
+        format!(
+            "In {}, the digit {} can only go in {}, so {} must be {}.",
+            house, num, cell, cell, num
+        )
+    }
+    fn naked_single(&self, cell: &str, num: i8) -> String {
+        format!(
+            "{} has {} as its only remaining candidate, so {} must be {}.",
+            cell, num, cell, num
+        )
+    }
+    fn pointing(&self, block: &str, num: i8, line: &str, rem_cells: &str) -> String {
+        format!(
+            "In {}, the digit {} can only go in {}, so {} can be removed from {}.",
+            block, num, line, num, rem_cells
+        )
+    }
+    fn claiming(&self, line: &str, num: i8, block: &str, rem_cells: &str) -> String {
+        format!(
+            "In {}, the digit {} can only go in {}, so {} can be removed from {}.",
+            line, num, block, num, rem_cells
+        )
+    }
+    fn naked_subset(&self, house: &str, cells: &str, nums: &str, removals: &str) -> String {
+        format!(
+            "In {}, the cells {} together only contain the candidates {}, \
+            so these digits cannot go anywhere else in {}: {}.",
+            house, cells, nums, house, removals
+        )
+    }
+    fn hidden_pair(&self, house: &str, nums: &str, cells: &str, removals: &str) -> String {
+        format!(
+            "In {}, the digits {} can only go in {}, \
+            so these cells cannot hold any other digit: {}.",
+            house, nums, cells, removals
+        )
+    }
+    fn fish(&self, name: &str, num: i8, base: &str, cover: &str, rem_cells: &str) -> String {
+        format!(
+            "The candidates {} in {} all lie in {}, forming {}, so {} can be removed from {}.",
+            num, base, cover, name, num, rem_cells
+        )
+    }
+}
+
+pub struct Chinese;
+
+impl MessageCatalog for Chinese {
+    fn house(&self, house: House) -> String {
+        match house {
+            House::Row(r) => format!("第{}行", r + 1),
+            House::Column(c) => format!("第{}列", c + 1),
+            House::Block(b) => format!("第{}宫", b + 1),
+        }
+    }
+    fn list(&self, items: &[String]) -> String {
+        match items {
+            [] => String::new(),
+            [item] => item.clone(),
+            [init @ .., last] => format!("{}和{}", init.join("、"), last),
+        }
+    }
+    fn fish_name(&self, size: usize) -> String {
+        match size {
+            2 => "X-Wing",
+            3 => "剑鱼",
+            4 => "水母",
+            _ => "鱼",
+        }
+        .to_string()
+    }
+    fn removal(&self, nums: &str, cells: &str) -> String {
+        format!("从{}中删去{}", cells, nums)
+    }
+    fn removals(&self, removals: &[String]) -> String {
+        removals.join("；")
+    }
+    fn hidden_single(&self, house: &str, num: i8, cell: &str) -> String {
+        format!(
+            "在{}中，数字{}只能填在{}，所以{}填{}。",
+            house, num, cell, cell, num
+        )
+    }
+    fn naked_single(&self, cell: &str, num: i8) -> String {
+        format!("{}只剩下候选数{}，所以{}填{}。", cell, num, cell, num)
+    }
+    fn pointing(&self, block: &str, num: i8, line: &str, rem_cells: &str) -> String {
+        format!(
+            "在{}中，数字{}只能出现在{}，所以可以从{}中删去候选数{}。",
+            block, num, line, rem_cells, num
+        )
+    }
+    fn claiming(&self, line: &str, num: i8, block: &str, rem_cells: &str) -> String {
+        format!(
+            "在{}中，数字{}只能出现在{}，所以可以从{}中删去候选数{}。",
+            line, num, block, rem_cells, num
+        )
+    }
+    fn naked_subset(&self, house: &str, cells: &str, nums: &str, removals: &str) -> String {
+        format!(
+            "在{}中，{}的候选数只有{}，这些数字不能出现在{}的其他格子中：{}。",
+            house, cells, nums, house, removals
+        )
+    }
+    fn hidden_pair(&self, house: &str, nums: &str, cells: &str, removals: &str) -> String {
+        format!(
+            "在{}中，数字{}只能出现在{}，所以这两格不能填其他数字：{}。",
+            house, nums, cells, removals
+        )
+    }
+    fn fish(&self, name: &str, num: i8, base: &str, cover: &str, rem_cells: &str) -> String {
+        format!(
+            "{}中的候选数{}都位于{}，构成{}，所以可以从{}中删去候选数{}。",
+            base, num, cover, name, rem_cells, num
+        )
+    }
+}
+
+/// Explain in one sentence why the step found by a technique is valid.
+#[allow(clippy::type_complexity)]
+pub fn explain(info: &TechniqueInfo, catalog: &impl MessageCatalog) -> String {
+    let cells = |cells: &[(usize, usize)]| {
+        catalog.list(
+            &cells
+                .iter()
+                .map(|cell| catalog.cell(*cell))
+                .collect::<Vec<_>>(),
+        )
+    };
+    let nums =
+        |nums: &[i8]| catalog.list(&nums.iter().map(|num| num.to_string()).collect::<Vec<_>>());
+    let houses = |houses: &[House]| {
+        catalog.list(
+            &houses
+                .iter()
+                .map(|house| catalog.house(*house))
+                .collect::<Vec<_>>(),
+        )
+    };
+    let removals = |removes: &[(&[(usize, usize)], &[i8])]| {
+        catalog.removals(
+            &removes
+                .iter()
+                .filter(|(rem_cells, _)| !rem_cells.is_empty())
+                .map(|(rem_cells, rem_nums)| catalog.removal(&nums(rem_nums), &cells(rem_cells)))
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    match info {
+        TechniqueInfo::HiddenSingle(info) => {
+            let (r, c, num) = info.fillable;
+            catalog.hidden_single(&catalog.house(info.house), num, &catalog.cell((r, c)))
+        }
+        TechniqueInfo::NakedSingle(info) => {
+            let (r, c, num) = info.0;
+            catalog.naked_single(&catalog.cell((r, c)), num)
+        }
+        TechniqueInfo::Pointing(info) => catalog.pointing(
+            &catalog.house(House::Block(info.block)),
+            info.rem_num,
+            &catalog.house(info.rem_house),
+            &cells(&info.rem_cells),
+        ),
+        TechniqueInfo::Claiming(info) => catalog.claiming(
+            &catalog.house(info.house),
+            info.rem_num,
+            &catalog.house(House::Block(info.rem_block)),
+            &cells(&info.rem_cells),
+        ),
+        TechniqueInfo::NakedPair(info) => catalog.naked_subset(
+            &catalog.house(info.house),
+            &cells(&info.cells),
+            &nums(&[info.rem_num_1, info.rem_num_2]),
+            &removals(&[
+                (&info.rem_cells_1, &[info.rem_num_1]),
+                (&info.rem_cells_2, &[info.rem_num_2]),
+            ]),
+        ),
+        TechniqueInfo::HiddenPair(info) => catalog.hidden_pair(
+            &catalog.house(info.house),
+            &nums(&info.nums),
+            &cells(&[info.rem_cell_1, info.rem_cell_2]),
+            &removals(&[
+                (&[info.rem_cell_1], &info.rem_nums_1),
+                (&[info.rem_cell_2], &info.rem_nums_2),
+            ]),
+        ),
+        TechniqueInfo::NakedSubset(info) => catalog.naked_subset(
+            &catalog.house(info.house),
+            &cells(&info.cells),
+            &nums(&info.nums),
+            &removals(
+                &info
+                    .removes
+                    .iter()
+                    .map(|(rem_cells, rem_num)| {
+                        (rem_cells.as_slice(), std::slice::from_ref(rem_num))
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        TechniqueInfo::Fish(info) => catalog.fish(
+            &catalog.fish_name(info.size),
+            info.candidate,
+            &houses(&info.base_set),
+            &houses(&info.cover_set),
+            &cells(&info.rem_cells),
+        ),
+    }
+}
+
+impl Display for TechniqueInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", explain(self, &English))
+    }
+}
@@ -1,12 +1,387 @@
-use rand::random;
+use std::{fmt::Display, ops::Range};
+
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 
 use crate::{
-    solver::{advanced::AdvancedSolver, stochastic::StochasticSolver},
+    analysis::solves_with,
+    budget::{Budget, Interrupted},
+    solver::{advanced::AdvancedSolver, bitboard::BitboardSolver, stochastic::StochasticSolver},
+    state::{full_state::FullState, simple_state::SimpleState},
+    techniques::TechniqueId,
     Grid,
 };
 
-use super::solver::{Grader, Solver};
+use super::solver::{Grader, RandomizedSolver, SolutionCount, Solver};
+
+/// The named difficulty tiers, ordered from the easiest to the hardest.
+/// Every tier covers a range of the score given by `AdvancedSolver` with the default config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Difficulty {
+    ExtraEasy,
+    Easy,
+    Normal,
+    Hard,
+    ExtraHard,
+    Ultimate,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 6] = [
+        Difficulty::ExtraEasy,
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::ExtraHard,
+        Difficulty::Ultimate,
+    ];
+
+    /// The scores of puzzles in this tier, including the start but not the end.
+    pub fn score_range(self) -> Range<f32> {
+        match self {
+            Difficulty::ExtraEasy => 0.0..54.0,
+            Difficulty::Easy => 54.0..55.0 * 1.5,
+            Difficulty::Normal => 55.0 * 1.5..55.0 * 2.0 * 3.4f32.ln(),
+            Difficulty::Hard => 55.0 * 2.0 * 3.4f32.ln()..55.0 * 2.6 * 6.0f32.ln(),
+            Difficulty::ExtraHard => 55.0 * 2.6 * 6.0f32.ln()..55.0 * 3.2 * 12.0f32.ln(),
+            Difficulty::Ultimate => 55.0 * 3.2 * 12.0f32.ln()..f32::INFINITY,
+        }
+    }
+
+    pub fn from_score(score: f32) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| score < difficulty.score_range().end)
+            .unwrap_or(Difficulty::Ultimate)
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Difficulty::ExtraEasy => "Extra Easy",
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::ExtraHard => "Extra Hard",
+            Difficulty::Ultimate => "Ultimate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Rate `puzzle`, which should have a unique solution, the same way as the generator does.
+/// The hand-picked puzzles returned for [`Difficulty::Ultimate`] are always `Ultimate`.
+pub fn classify(puzzle: &Grid) -> Difficulty {
+    if (0..ULTIMATE_PUZZLES.len()).any(|i| ultimate_puzzle(i) == *puzzle) {
+        return Difficulty::Ultimate;
+    }
+    let mut solver = AdvancedSolver::<FullState>::from(*puzzle);
+    solver.have_unique_solution();
+    Difficulty::from_score(solver.difficulty())
+}
+
+/// A symmetry of the clue layout: a clue is kept exactly when all of its images are.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// Unchanged by a half turn around the center.
+    Rotational180,
+    /// Unchanged by a quarter turn around the center.
+    Rotational90,
+    /// Unchanged by swapping the left and the right.
+    Mirror,
+    /// Unchanged by reflecting across the main diagonal, from R1C1 to R9C9.
+    Diagonal,
+    /// Unchanged by reflecting across the anti-diagonal, from R1C9 to R9C1.
+    AntiDiagonal,
+}
+
+impl Symmetry {
+    /// The cells mapped to each other by the symmetry, including `(r, c)` itself, without repetition.
+    pub fn orbit(self, r: usize, c: usize) -> Vec<(usize, usize)> {
+        let mut orbit = match self {
+            Symmetry::None => vec![(r, c)],
+            Symmetry::Rotational180 => vec![(r, c), (8 - r, 8 - c)],
+            Symmetry::Rotational90 => vec![(r, c), (c, 8 - r), (8 - r, 8 - c), (8 - c, r)],
+            Symmetry::Mirror => vec![(r, c), (r, 8 - c)],
+            Symmetry::Diagonal => vec![(r, c), (c, r)],
+            Symmetry::AntiDiagonal => vec![(r, c), (8 - c, 8 - r)],
+        };
+        orbit.sort();
+        orbit.dedup();
+        orbit
+    }
+
+    /// Return whether the clues of `puzzle` are laid out with this symmetry.
+    pub fn is_symmetric(self, puzzle: &Grid) -> bool {
+        (0..81).all(|i| {
+            let (r, c) = (i / 9, i % 9);
+            self.orbit(r, c)
+                .iter()
+                .all(|(r2, c2)| (puzzle.0[r][c] == 0) == (puzzle.0[*r2][*c2] == 0))
+        })
+    }
+}
+
+/// Options of [`generate`].
+#[derive(Clone, Debug, Default)]
+pub struct GenConfig {
+    budget: Budget,
+    seed: Option<u64>,
+    symmetry: Symmetry,
+}
+
+impl GenConfig {
+    /// Give up once `budget` runs out.
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Take all the randomness from a generator seeded with `seed`,
+    /// so the same seed always gives the same puzzle.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Lay the clues out with `symmetry`.
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+}
+
+/// Generate a puzzle with a unique solution in the tier `difficulty`, see [`classify`].
+///
+/// ```
+/// use sudoku::generator::{classify, generate, Difficulty, GenConfig};
+///
+/// let puzzle = generate(Difficulty::Easy, &GenConfig::default().seed(42)).unwrap();
+/// assert_eq!(classify(&puzzle), Difficulty::Easy);
+/// ```
+pub fn generate(difficulty: Difficulty, config: &GenConfig) -> Result<Grid, Interrupted> {
+    match config.seed {
+        Some(seed) => generate_with_rng(difficulty, config, &mut StdRng::seed_from_u64(seed)),
+        None => generate_with_rng(difficulty, config, &mut thread_rng()),
+    }
+}
+
+/// Same as [`generate`], taking all the randomness from `rng` instead of the seed in `config`.
+pub fn generate_with_rng(
+    difficulty: Difficulty,
+    config: &GenConfig,
+    rng: &mut impl Rng,
+) -> Result<Grid, Interrupted> {
+    let min_blank_cnt = match difficulty {
+        Difficulty::ExtraEasy => 35 + rng.gen_range(0..10),
+        Difficulty::Easy => 45 + rng.gen_range(0..10),
+        Difficulty::Normal => 55,
+        Difficulty::Hard => 50,
+        Difficulty::ExtraHard => 45,
+        Difficulty::Ultimate => return Ok(random_sudoku_puzzle_ultimate_with_rng(rng)),
+    };
+    // 空格较少的简单题目，难度上限再随空格数收紧一些
+    let range = difficulty.score_range();
+    let (min_difficulty, max_difficulty) = match difficulty {
+        Difficulty::ExtraEasy => (range.start, range.end.min(min_blank_cnt as f32 * 1.3)),
+        Difficulty::Easy => (
+            range.start.max(min_blank_cnt as f32 * 1.2),
+            range.end.min(min_blank_cnt as f32 * 1.5),
+        ),
+        _ => (range.start, range.end),
+    };
+    dig::<BitboardSolver, _>(
+        new_grid,
+        min_blank_cnt,
+        |puzzle, _| {
+            // 极易的题目必须只用 Hidden Single 就能解出，难度分数偶尔会放过用到其他技巧的题目
+            Ok(difficulty_between::<AdvancedSolver, f32>(
+                puzzle,
+                &min_difficulty,
+                &max_difficulty.next_down(),
+                &config.budget,
+            )? && (difficulty != Difficulty::ExtraEasy
+                || solves_with(puzzle, &[TechniqueId::HiddenSingle])))
+        },
+        config.symmetry,
+        &config.budget,
+        rng,
+    )
+}
+
+// generate_requiring 至少挖掉的空格数，空格太少的题目很少用到单数以外的技巧
+const REQUIRING_MIN_BLANK_CNT: i32 = 45;
+
+/// Generate a puzzle with a unique solution whose logical solve, always trying the easier of
+/// [`TechniqueId::DEFAULT`] first, needs `technique` at least once and nothing harder.
+/// A variant such as [`TechniqueId::NakedPairRow`] stands for its whole
+/// [family](TechniqueId::family).
+///
+/// Puzzles needing the rarest techniques, like [`TechniqueId::Jellyfish`], may take
+/// arbitrarily long to come up, so give `config` a budget for them.
+///
+/// ```
+/// use sudoku::{
+///     analysis::solves_with,
+///     generator::{generate_requiring, GenConfig},
+///     techniques::TechniqueId,
+/// };
+///
+/// let puzzle = generate_requiring(TechniqueId::Pointing, &GenConfig::default()).unwrap();
+/// assert!(!solves_with(&puzzle, &[TechniqueId::HiddenSingle, TechniqueId::NakedSingle]));
+/// ```
+pub fn generate_requiring(technique: TechniqueId, config: &GenConfig) -> Result<Grid, Interrupted> {
+    match config.seed {
+        Some(seed) => {
+            generate_requiring_with_rng(technique, config, &mut StdRng::seed_from_u64(seed))
+        }
+        None => generate_requiring_with_rng(technique, config, &mut thread_rng()),
+    }
+}
+
+/// Same as [`generate_requiring`], taking all the randomness from `rng` instead of the seed in `config`.
+pub fn generate_requiring_with_rng(
+    technique: TechniqueId,
+    config: &GenConfig,
+    rng: &mut impl Rng,
+) -> Result<Grid, Interrupted> {
+    let family = technique.family();
+    let easier: Vec<TechniqueId> = TechniqueId::DEFAULT
+        .into_iter()
+        .take_while(|id| *id != family)
+        .collect();
+    let allowed = [easier.as_slice(), &[family]].concat();
+    dig::<BitboardSolver, _>(
+        new_grid,
+        REQUIRING_MIN_BLANK_CNT,
+        |puzzle, _| Ok(solves_with(puzzle, &allowed) && !solves_with(puzzle, &easier)),
+        config.symmetry,
+        &config.budget,
+        rng,
+    )
+}
+
+/// Why [`generate_with_mask`] failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaskError {
+    /// The mask has this many cells, fewer than the 17 clues every puzzle
+    /// with a unique solution needs.
+    TooFewClues(usize),
+    Interrupted(Interrupted),
+}
+
+impl Display for MaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaskError::TooFewClues(cnt) => {
+                write!(f, "a mask of {} cells cannot give a unique solution", cnt)
+            }
+            MaskError::Interrupted(interrupted) => write!(f, "{}", interrupted),
+        }
+    }
+}
+
+impl std::error::Error for MaskError {}
+
+impl From<Interrupted> for MaskError {
+    fn from(interrupted: Interrupted) -> Self {
+        Self::Interrupted(interrupted)
+    }
+}
+
+// 遮罩题目的解数最多数到这里，上限越高越能分辨两个题目哪个离唯一解更近
+const MASK_SOLUTION_CNT_LIMIT: u64 = 64;
+// 连续这么多次改动都没有减少解数时，换一个终局重新开始
+const MASK_STALL_LIMIT: u32 = 2000;
+
+/// Generate a puzzle with a unique solution whose clues are exactly the cells set in `mask`,
+/// to draw a shape with the clues. The symmetry in `config` is ignored.
+///
+/// Starting from the mask's digits of a random full grid, the generator keeps changing one
+/// clue at a time as long as the puzzle stays solvable and does not get more solutions,
+/// until the solution is unique. Masks with less than 17 cells fail at once. Some larger
+/// masks cannot have a unique solution either, and sparse ones take long,
+/// so give `config` a budget unless the mask is known to be easy.
+///
+/// ```
+/// use std::time::Duration;
+/// use sudoku::{
+///     budget::Budget,
+///     generator::{generate_with_mask, random_sudoku_puzzle_easy, GenConfig},
+/// };
+///
+/// // the shape of another puzzle
+/// let shape = random_sudoku_puzzle_easy();
+/// let mask = shape.0.map(|row| row.map(|num| num > 0));
+/// let config = GenConfig::default().budget(Budget::default().timeout(Duration::from_secs(60)));
+/// let puzzle = generate_with_mask(mask, &config).unwrap();
+/// assert!((0..81).all(|i| (puzzle.0[i / 9][i % 9] > 0) == mask[i / 9][i % 9]));
+/// ```
+pub fn generate_with_mask(mask: [[bool; 9]; 9], config: &GenConfig) -> Result<Grid, MaskError> {
+    match config.seed {
+        Some(seed) => generate_with_mask_with_rng(mask, config, &mut StdRng::seed_from_u64(seed)),
+        None => generate_with_mask_with_rng(mask, config, &mut thread_rng()),
+    }
+}
+
+/// Same as [`generate_with_mask`], taking all the randomness from `rng` instead of the seed in `config`.
+pub fn generate_with_mask_with_rng(
+    mask: [[bool; 9]; 9],
+    config: &GenConfig,
+    rng: &mut impl Rng,
+) -> Result<Grid, MaskError> {
+    let cells: Vec<(usize, usize)> = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .filter(|(r, c)| mask[*r][*c])
+        .collect();
+    if cells.len() < 17 {
+        return Err(MaskError::TooFewClues(cells.len()));
+    }
+    let solution_cnt = |puzzle: &Grid| -> Result<u64, Interrupted> {
+        match BitboardSolver::from(*puzzle)
+            .try_count_solutions(MASK_SOLUTION_CNT_LIMIT, &config.budget)?
+        {
+            SolutionCount::Exact(cnt) | SolutionCount::AtLeast(cnt) => Ok(cnt),
+        }
+    };
+    loop {
+        // 随机终局只保留遮罩内的数字，题目一定有解
+        let mut puzzle = new_grid(rng);
+        for (row, mask_row) in puzzle.0.iter_mut().zip(mask) {
+            for (num, keep) in row.iter_mut().zip(mask_row) {
+                if !keep {
+                    *num = 0;
+                }
+            }
+        }
+        // 局部搜索：随机改动一个线索，只要题目仍然有解且解数没有变多就保留，直到解唯一
+        let mut cnt = solution_cnt(&puzzle)?;
+        let mut stalled = 0;
+        while cnt > 1 && stalled < MASK_STALL_LIMIT {
+            config.budget.check()?;
+            let (r, c) = cells[rng.gen_range(0..cells.len())];
+            let old = puzzle.0[r][c];
+            puzzle.0[r][c] = (old + rng.gen_range(0..8)) % 9 + 1;
+            let new_cnt = solution_cnt(&puzzle)?;
+            if new_cnt > 0 && new_cnt <= cnt {
+                stalled = if new_cnt < cnt { 0 } else { stalled + 1 };
+                cnt = new_cnt;
+            } else {
+                puzzle.0[r][c] = old;
+                stalled += 1;
+            }
+        }
+        if cnt == 1 {
+            return Ok(puzzle);
+        }
+    }
+}
 
+/// Dig a full grid made by `S1` until the puzzle has `min_blank_cnt` blanks and a difficulty
+/// between the bounds. `S2` both checks the uniqueness of every dug puzzle and rates it,
+/// so a fast solver like `BitboardSolver` makes the generator much faster.
 pub fn random_sudoku_puzzle<S1, S2, T>(
     min_blank_cnt: i32, // 需要生成的题目最少空格数
     min_difficulty: T,  // 题目最小难度分数
@@ -16,78 +391,197 @@ where
     S1: Solver + From<Grid>,
     S2: Solver + Grader<T> + From<Grid>,
     T: PartialOrd + From<i8>,
+{
+    try_random_sudoku_puzzle::<S1, S2, T>(
+        min_blank_cnt,
+        min_difficulty,
+        max_difficulty,
+        &Budget::default(),
+    )
+    .expect("an unlimited budget never runs out")
+}
+
+/// Like `random_sudoku_puzzle`, but gives up once `budget` runs out.
+/// The budget is checked before every try of digging and during every solve.
+pub fn try_random_sudoku_puzzle<S1, S2, T>(
+    min_blank_cnt: i32,
+    min_difficulty: T,
+    max_difficulty: T,
+    budget: &Budget,
+) -> Result<Grid, Interrupted>
+where
+    S1: Solver + From<Grid>,
+    S2: Solver + Grader<T> + From<Grid>,
+    T: PartialOrd + From<i8>,
+{
+    dig(
+        |_| S1::from(Grid([[0; 9]; 9])).any_solution().unwrap(),
+        min_blank_cnt,
+        |_, solver: &S2| Ok(in_range(solver, &min_difficulty, &max_difficulty)),
+        Symmetry::None,
+        budget,
+        &mut thread_rng(),
+    )
+}
+
+/// Like `random_sudoku_puzzle`, but takes all the randomness from `rng`,
+/// so the same seed always gives the same puzzle.
+pub fn random_sudoku_puzzle_with_rng<S1, S2, T>(
+    min_blank_cnt: i32,
+    min_difficulty: T,
+    max_difficulty: T,
+    rng: &mut impl Rng,
+) -> Grid
+where
+    S1: RandomizedSolver + From<Grid>,
+    S2: Solver + Grader<T> + From<Grid>,
+    T: PartialOrd + From<i8>,
+{
+    dig(
+        |rng| {
+            S1::from(Grid([[0; 9]; 9]))
+                .with_rng(rng)
+                .any_solution()
+                .unwrap()
+        },
+        min_blank_cnt,
+        |_, solver: &S2| Ok(in_range(solver, &min_difficulty, &max_difficulty)),
+        Symmetry::None,
+        &Budget::default(),
+        rng,
+    )
+    .expect("an unlimited budget never runs out")
+}
+
+fn new_grid(rng: &mut impl Rng) -> Grid {
+    StochasticSolver::<SimpleState>::from(Grid([[0; 9]; 9]))
+        .with_rng(rng)
+        .any_solution()
+        .unwrap()
+}
+
+fn in_range<S2, T>(solver: &S2, min_difficulty: &T, max_difficulty: &T) -> bool
+where
+    S2: Grader<T>,
+    T: PartialOrd,
+{
+    let difficulty = solver.difficulty();
+    difficulty >= *min_difficulty && difficulty <= *max_difficulty
+}
+
+// 题目已知有唯一解，找到一个解即可评分
+fn difficulty_between<S2, T>(
+    puzzle: &Grid,
+    min_difficulty: &T,
+    max_difficulty: &T,
+    budget: &Budget,
+) -> Result<bool, Interrupted>
+where
+    S2: Solver + Grader<T> + From<Grid>,
+    T: PartialOrd,
+{
+    let mut solver = S2::from(*puzzle);
+    solver.try_any_solution(budget)?;
+    Ok(in_range(&solver, min_difficulty, max_difficulty))
+}
+
+// 由 new_grid 生成随机终局，再按 symmetry 成组随机挖空，直到题目有唯一解、空格数足够且被 accept 接受。
+// 唯一解由求解器 U 判断，accept 还会拿到这个刚刚求解过的求解器
+fn dig<U, R>(
+    mut new_grid: impl FnMut(&mut R) -> Grid,
+    min_blank_cnt: i32,
+    mut accept: impl FnMut(&Grid, &U) -> Result<bool, Interrupted>,
+    symmetry: Symmetry,
+    budget: &Budget,
+    rng: &mut R,
+) -> Result<Grid, Interrupted>
+where
+    U: Solver + From<Grid>,
+    R: Rng,
 {
     loop {
         // 生成随机终局
-        let mut puzzle = S1::from(Grid([[0; 9]; 9])).any_solution().unwrap();
+        let mut puzzle = new_grid(rng);
 
         let mut dug = 0; // 已经挖掉的空格数
-        let mut trace = vec![]; // 挖空历史记录
-        trace.reserve(64);
+        let mut trace: Vec<Vec<(usize, usize, i8)>> = Vec::with_capacity(64); // 挖空历史记录，每项为一组对称的格子
         let failed_try_threshold = 48; // 挖空失败次数阈值，失败次数超过此值会尝试回退
 
         let trace_back_step = 24; // 回退的步长
         let mut trace_back_cnt = 0; // 回退的次数
         let trace_back_cnt_threshold = 12; // 回退次数阈值，回退次数超过此值会尝试重新生成终局
 
-        let mut difficulty: T = 0.into(); // 难度分数
+        let mut accepted = false; // 题目是否被接受
 
-        while trace_back_cnt < trace_back_cnt_threshold
-            && !(dug >= min_blank_cnt
-                && (difficulty >= min_difficulty && difficulty <= max_difficulty))
-        {
+        while trace_back_cnt < trace_back_cnt_threshold && !(dug >= min_blank_cnt && accepted) {
             let mut failed_try = 0;
-            let step = match dug {
-                ..=40 => 3,
-                41.. => 1,
+            // 对称时一组已有多个格子，每次只挖一组
+            let step = match (symmetry, dug) {
+                (Symmetry::None, ..=40) => 3,
+                _ => 1,
             };
+            let mut removed; // 这一次挖掉的格子数
             loop {
-                // 一次挖 step 个空
+                budget.check()?;
+
+                // 一次挖 step 组空
+                removed = 0;
                 for _ in 0..step {
-                    // 随机选取非空格
-                    let (mut r, mut c) = (random::<usize>() % 9, random::<usize>() % 9);
+                    // 随机选取非空格，连同与它对称的格子一起挖掉
+                    let (mut r, mut c) = (rng.gen::<usize>() % 9, rng.gen::<usize>() % 9);
                     while puzzle.0[r][c] == 0 {
-                        (r, c) = (random::<usize>() % 9, random::<usize>() % 9);
+                        (r, c) = (rng.gen::<usize>() % 9, rng.gen::<usize>() % 9);
                     }
-                    trace.push((r, c, puzzle.0[r][c]));
-                    puzzle.0[r][c] = 0;
+                    let orbit: Vec<_> = symmetry
+                        .orbit(r, c)
+                        .into_iter()
+                        .map(|(r, c)| (r, c, puzzle.0[r][c]))
+                        .collect();
+                    for (r, c, _) in &orbit {
+                        puzzle.0[*r][*c] = 0;
+                    }
+                    removed += orbit.len() as i32;
+                    trace.push(orbit);
                 }
 
-                // 挖空后，判断是否有唯一解
-                let mut solver = S2::from(puzzle);
-                if solver.have_unique_solution() {
-                    difficulty = solver.difficulty();
+                // 挖空后，先判断是否有唯一解，空格数达到要求后再检查题目能否被接受
+                let mut solver = U::from(puzzle);
+                if solver.try_count_solutions(2, budget)? == SolutionCount::Exact(1) {
+                    if dug + removed >= min_blank_cnt {
+                        accepted = accept(&puzzle, &solver)?;
+                    }
                     break;
                 }
 
                 // 没有唯一解，填回刚刚挖的空
                 failed_try += 1;
                 for _ in 0..step {
-                    let last = trace.pop();
-                    if last.is_some() {
-                        let (r, c, num) = last.unwrap();
+                    for (r, c, num) in trace.pop().unwrap_or_default() {
                         puzzle.0[r][c] = num;
                     }
                 }
 
                 // 尝试失败次数过多时，退回一定步数重新尝试
                 if failed_try > failed_try_threshold {
-                    for _ in 0..trace_back_step {
-                        let last = trace.pop();
-                        if last.is_some() {
-                            let (r, c, num) = last.unwrap();
-                            puzzle.0[r][c] = num;
+                    let mut restored = 0;
+                    while restored < trace_back_step {
+                        let Some(orbit) = trace.pop() else {
+                            break;
+                        };
+                        for (r, c, num) in &orbit {
+                            puzzle.0[*r][*c] = *num;
                         }
+                        restored += orbit.len() as i32;
                     }
-                    dug -= trace_back_step;
+                    dug -= restored;
                     failed_try = 0;
                     trace_back_cnt += 1;
                 }
             }
-            dug += step;
+            dug += removed;
         }
-        if dug >= min_blank_cnt && (difficulty >= min_difficulty && difficulty <= max_difficulty) {
-            return puzzle;
+        if dug >= min_blank_cnt && accepted {
+            return Ok(puzzle);
         }
     }
 }
@@ -95,108 +589,77 @@ where
 /// Return one of the simplest sudoku puzzles which can be solved
 /// only using **Hidden Single** (especially **Hidden Single in Block**)
 /// and have quite few (35 ~ 44) blanks.
+///
+/// The score is below both 1.3 times the blank count and
+/// the lower bound of [`Difficulty::Easy`] (54).
 pub fn random_sudoku_puzzle_extraeasy() -> Grid {
-    let min_blank_cnt = 35 + random::<i32>() % 10;
-    random_sudoku_puzzle::<StochasticSolver, AdvancedSolver, f32>(
-        min_blank_cnt,
-        0.0,
-        min_blank_cnt as f32 * 1.3,
-    )
+    random_sudoku_puzzle_extraeasy_with_rng(&mut thread_rng())
+}
+
+/// Same as [`random_sudoku_puzzle_extraeasy`], taking all the randomness from `rng`.
+pub fn random_sudoku_puzzle_extraeasy_with_rng(rng: &mut impl Rng) -> Grid {
+    generate_with_rng(Difficulty::ExtraEasy, &GenConfig::default(), rng)
+        .expect("an unlimited budget never runs out")
 }
 
 /// Return a sudoku puzzle which can be solved almost only using **Hidden Single**
 /// and have 45 ~ 54 blanks.
 pub fn random_sudoku_puzzle_easy() -> Grid {
-    let min_blank_cnt = 45 + random::<i32>() % 10;
-    random_sudoku_puzzle::<StochasticSolver, AdvancedSolver, f32>(
-        min_blank_cnt,
-        min_blank_cnt as f32 * 1.2,
-        min_blank_cnt as f32 * 1.5,
-    )
+    random_sudoku_puzzle_easy_with_rng(&mut thread_rng())
+}
+
+/// Same as [`random_sudoku_puzzle_easy`], taking all the randomness from `rng`.
+pub fn random_sudoku_puzzle_easy_with_rng(rng: &mut impl Rng) -> Grid {
+    generate_with_rng(Difficulty::Easy, &GenConfig::default(), rng)
+        .expect("an unlimited budget never runs out")
 }
 
 /// Return a sudoku puzzle with normal difficulty.
 /// Generally it can be solved with some normal techniques
 /// like **Locked Candidates** and **Hidden/Naked Subsets**.
 pub fn random_sudoku_puzzle_normal() -> Grid {
-    random_sudoku_puzzle::<StochasticSolver, AdvancedSolver, f32>(
-        55,
-        55.0 * 1.5,
-        55.0 * 2.0 * 3.4f32.ln(),
-    )
+    random_sudoku_puzzle_normal_with_rng(&mut thread_rng())
+}
+
+/// Same as [`random_sudoku_puzzle_normal`], taking all the randomness from `rng`.
+pub fn random_sudoku_puzzle_normal_with_rng(rng: &mut impl Rng) -> Grid {
+    generate_with_rng(Difficulty::Normal, &GenConfig::default(), rng)
+        .expect("an unlimited budget never runs out")
 }
 
 pub fn random_sudoku_puzzle_hard() -> Grid {
-    random_sudoku_puzzle::<StochasticSolver, AdvancedSolver, f32>(
-        50,
-        55.0 * 2.0 * 3.4f32.ln(),
-        55.0 * 2.6 * 6.0f32.ln(),
-    )
+    random_sudoku_puzzle_hard_with_rng(&mut thread_rng())
+}
+
+/// Same as [`random_sudoku_puzzle_hard`], taking all the randomness from `rng`.
+pub fn random_sudoku_puzzle_hard_with_rng(rng: &mut impl Rng) -> Grid {
+    generate_with_rng(Difficulty::Hard, &GenConfig::default(), rng)
+        .expect("an unlimited budget never runs out")
 }
 
 pub fn random_sudoku_puzzle_extrahard() -> Grid {
-    random_sudoku_puzzle::<StochasticSolver, AdvancedSolver, f32>(
-        45,
-        55.0 * 2.6 * 6.0f32.ln(),
-        55.0 * 3.2 * 12.0f32.ln(),
-    )
+    random_sudoku_puzzle_extrahard_with_rng(&mut thread_rng())
+}
+
+/// Same as [`random_sudoku_puzzle_extrahard`], taking all the randomness from `rng`.
+pub fn random_sudoku_puzzle_extrahard_with_rng(rng: &mut impl Rng) -> Grid {
+    generate_with_rng(Difficulty::ExtraHard, &GenConfig::default(), rng)
+        .expect("an unlimited budget never runs out")
 }
 
 /// Return a sudoku puzzle which is impossible for human to solve.
 pub fn random_sudoku_puzzle_ultimate() -> Grid {
-    let puzzles: [&str; 50] = [
-        "500000300020100070008000009040007000000821000000600010300000800060004020009000005",
-        "800000009040001030007000600000023000050904020000105000006000700010300040900000008",
-        "000070100000008050020900003530000000062000004094600000000001800300200009000050070",
-        "000006080000100200009030005040070003000008010000200600071090000590000004804000000",
-        "000056000050109000000020040090040070006000300800000002300000008002000600070500010",
-        "500000004080006090001000200070308000000050000000790030002000100060900080400000005",
-        "070200009003060000400008000020900010800004000006030000000000600090000051000700002",
-        "100080000005900000070002000009500040800010000060007200000000710000004603030000402",
-        "000900100000080007004002050200005040000100900000070008605000030300006000070000006",
-        "000001080030500200000040006200300500000008010000060004050000700300970000062000000",
-        "800000005040003020007000100000004000090702060000639000001000700030200040500000008",
-        "900000001030004070006000200050302000000060000000078050002000600040700030100000009",
-        "500000008030007040001000900020603000000725000000800060009000100070400030800000005",
-        "400000009070008030006000100050702000000065000000003020001000600080300070900000004",
-        "100006009007080030000200400000500070300001002000090600060003050004000000900020001",
-        "800000001050009040003000600070056000000980000000704020006000300090400050100000008",
-        "010000009005080700300700060004250000000000000000840200008007500600000030090000001",
-        "300000005020007040001000900080036000000028000000704060009000100070400020500000003",
-        "400000003080002060007000900010508000000701000000026050009000700020600080300000004",
-        "600005020040700000009080000010000302000000087000200104070400003500006000008090000",
-        "007002000500090400010600000400050003060100000002007000000000810900000306000080059",
-        "000007090000800400003060001420010000031000002605000000060400800500020006000009070",
-        "000600001000020400300009050090005030000040200000100006570008000002000000080000090",
-        "006003000900080200070400000003006000040700000800020090500000008000000709000510020",
-        "010300000000009000000710050004050900200000006070800030600000002080030070009000400",
-        "000008070000300200005040009260094000059000006401000000000200300100060004000007080",
-        "000800300000010005004002070200007040000300807000050001907000060600009000050000000",
-        "800000007040001030009000600000532000050108020000400000006000900010300040700000008",
-        "400000008050002090001000600070503000000060000000790030006000100020900050800000004",
-        "300000009010006050002000400070060000000701000000845070004000200060500010900000003",
-        "000000789000100036009000010200030000070004000008500100300020000005800090040007000",
-        "100000000006700020080030500007060030000500008000004900300800600002090070040000001",
-        "700000005040001030002000900060008000000946000000103080009000200010300040500000007",
-        "001020000300004000050600070080900005002003000400010000070000038000800069000000200",
-        "007580000000030000000076005400000020090000100003060008010600900006800003200000040",
-        "097000000301005000045000800003008400000020060000100009700004300000900001000060020",
-        "003700000050004000100020080900000012000000400080010090007300000200090006040005000",
-        "000000100600000874000007026030400000005090000100008002009050000200001008040300000",
-        "100000004020006090005000800030650000000372000000098070008000500060900020400000001",
-        "005300000800000020070010500400005300010070006003200080060500009004000030000009700",
-        "000002005006700400000009008070090000600400700010000080060300100300000002400005000",
-        "020000600400080007009000010005006000300040900010200000000700004000001050800090300",
-        "900000007030008040006000200010389000000010000000205010002000600080400030700000009",
-        "002400006030010000500008000007000002010000030900600400000007001000090080400200500",
-        "100300000020090400005007000800000100040000020007060003000400800000020090006005007",
-        "002600000030080000500009100006000002080000030700001400000004005010020080000700900",
-        "003500100040080000600009000800000002050700030001000400000006009000020080070100500",
-        "300000906040200080000060000050800020009000307000007000010042000000000010508100000",
-        "000090050010000030002300700004500070800000200000006400090010000080060000005400007",
-        "100500000200000030004060100006007000008000009400080200000009007040010600000005003",
-    ];
-    let puzzle_str = String::from(puzzles[random::<usize>() % puzzles.len()]);
+    random_sudoku_puzzle_ultimate_with_rng(&mut thread_rng())
+}
+
+/// Same as [`random_sudoku_puzzle_ultimate`], taking all the randomness from `rng`.
+pub fn random_sudoku_puzzle_ultimate_with_rng(rng: &mut impl Rng) -> Grid {
+    ultimate_puzzle(rng.gen_range(0..ULTIMATE_PUZZLES.len()))
+}
+
+#[allow(clippy::needless_range_loop)]
+fn ultimate_puzzle(i: usize) -> Grid {
+    let puzzle_str = String::from(ULTIMATE_PUZZLES[i]);
     let mut puzzle = [[0; 9]; 9];
     for r in 0..9 {
         for c in 0..9 {
@@ -205,3 +668,57 @@ pub fn random_sudoku_puzzle_ultimate() -> Grid {
     }
     Grid(puzzle)
 }
+
+// 人工挑选的极难题目，难度分数不能反映它们的难度
+const ULTIMATE_PUZZLES: [&str; 50] = [
+    "500000300020100070008000009040007000000821000000600010300000800060004020009000005",
+    "800000009040001030007000600000023000050904020000105000006000700010300040900000008",
+    "000070100000008050020900003530000000062000004094600000000001800300200009000050070",
+    "000006080000100200009030005040070003000008010000200600071090000590000004804000000",
+    "000056000050109000000020040090040070006000300800000002300000008002000600070500010",
+    "500000004080006090001000200070308000000050000000790030002000100060900080400000005",
+    "070200009003060000400008000020900010800004000006030000000000600090000051000700002",
+    "100080000005900000070002000009500040800010000060007200000000710000004603030000402",
+    "000900100000080007004002050200005040000100900000070008605000030300006000070000006",
+    "000001080030500200000040006200300500000008010000060004050000700300970000062000000",
+    "800000005040003020007000100000004000090702060000639000001000700030200040500000008",
+    "900000001030004070006000200050302000000060000000078050002000600040700030100000009",
+    "500000008030007040001000900020603000000725000000800060009000100070400030800000005",
+    "400000009070008030006000100050702000000065000000003020001000600080300070900000004",
+    "100006009007080030000200400000500070300001002000090600060003050004000000900020001",
+    "800000001050009040003000600070056000000980000000704020006000300090400050100000008",
+    "010000009005080700300700060004250000000000000000840200008007500600000030090000001",
+    "300000005020007040001000900080036000000028000000704060009000100070400020500000003",
+    "400000003080002060007000900010508000000701000000026050009000700020600080300000004",
+    "600005020040700000009080000010000302000000087000200104070400003500006000008090000",
+    "007002000500090400010600000400050003060100000002007000000000810900000306000080059",
+    "000007090000800400003060001420010000031000002605000000060400800500020006000009070",
+    "000600001000020400300009050090005030000040200000100006570008000002000000080000090",
+    "006003000900080200070400000003006000040700000800020090500000008000000709000510020",
+    "010300000000009000000710050004050900200000006070800030600000002080030070009000400",
+    "000008070000300200005040009260094000059000006401000000000200300100060004000007080",
+    "000800300000010005004002070200007040000300807000050001907000060600009000050000000",
+    "800000007040001030009000600000532000050108020000400000006000900010300040700000008",
+    "400000008050002090001000600070503000000060000000790030006000100020900050800000004",
+    "300000009010006050002000400070060000000701000000845070004000200060500010900000003",
+    "000000789000100036009000010200030000070004000008500100300020000005800090040007000",
+    "100000000006700020080030500007060030000500008000004900300800600002090070040000001",
+    "700000005040001030002000900060008000000946000000103080009000200010300040500000007",
+    "001020000300004000050600070080900005002003000400010000070000038000800069000000200",
+    "007580000000030000000076005400000020090000100003060008010600900006800003200000040",
+    "097000000301005000045000800003008400000020060000100009700004300000900001000060020",
+    "003700000050004000100020080900000012000000400080010090007300000200090006040005000",
+    "000000100600000874000007026030400000005090000100008002009050000200001008040300000",
+    "100000004020006090005000800030650000000372000000098070008000500060900020400000001",
+    "005300000800000020070010500400005300010070006003200080060500009004000030000009700",
+    "000002005006700400000009008070090000600400700010000080060300100300000002400005000",
+    "020000600400080007009000010005006000300040900010200000000700004000001050800090300",
+    "900000007030008040006000200010389000000010000000205010002000600080400030700000009",
+    "002400006030010000500008000007000002010000030900600400000007001000090080400200500",
+    "100300000020090400005007000800000100040000020007060003000400800000020090006005007",
+    "002600000030080000500009100006000002080000030700001400000004005010020080000700900",
+    "003500100040080000600009000800000002050700030001000400000006009000020080070100500",
+    "300000906040200080000060000050800020009000307000007000010042000000000010508100000",
+    "000090050010000030002300700004500070800000200000006400090010000080060000005400007",
+    "100500000200000030004060100006007000008000009400080200000009007040010600000005003",
+];
@@ -0,0 +1,46 @@
+use crate::{
+    state::{State, TrackingCandidateCountOfCell, TrackingCandidates, TrackingCellCountOfCandidate},
+    techniques::{House, Step, TechniqueId},
+};
+
+/// How much a hint gives away. Each level includes everything the previous ones give.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HintLevel {
+    /// Only the name of the technique to use.
+    Technique = 1,
+    /// Also the houses to look at.
+    Region = 2,
+    /// Also the cells forming the pattern.
+    Pattern = 3,
+    /// Also the exact placement or elimination.
+    Step = 4,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hint {
+    pub technique: TechniqueId,
+    pub houses: Option<Vec<House>>,
+    pub cells: Option<Vec<(usize, usize)>>,
+    pub step: Option<Step>,
+}
+
+/// Return a hint for the next move on `state`, revealing as much as `level` allows.
+///
+/// The easiest applicable technique (by score) is chosen.
+/// Return `None` if none of the techniques can make progress.
+pub fn hint<T>(state: &T, level: HintLevel) -> Option<Hint>
+where
+    T: State + TrackingCandidates + TrackingCandidateCountOfCell + TrackingCellCountOfCandidate,
+{
+    let deduction = TechniqueId::DEFAULT
+        .iter()
+        .filter_map(|technique| technique.analyze(state))
+        .min_by(|d1, d2| d1.score.total_cmp(&d2.score))?;
+
+    Some(Hint {
+        technique: deduction.technique,
+        houses: (level >= HintLevel::Region).then(|| deduction.info.houses()),
+        cells: (level >= HintLevel::Pattern).then(|| deduction.info.cells(state)),
+        step: (level >= HintLevel::Step).then_some(deduction.step),
+    })
+}
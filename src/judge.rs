@@ -1,6 +1,8 @@
+use std::fmt::Display;
+
 use crate::Grid;
 
-use super::utils::coord_2_block;
+use super::utils::{block_idx_2_coord, coord_2_block};
 
 // 返回 (
 //     board是否为有效的部分解
@@ -46,3 +48,88 @@ pub fn judge_sudoku(board: &Grid) -> (bool, bool, [[bool; 9]; 9]) {
     }
     (valid, valid && full, valid_cond)
 }
+
+/// Why a grid cannot be a sudoku puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// A cell holds a value outside 0 ~ 9.
+    OutOfRange { r: usize, c: usize, value: i8 },
+    /// The same digit appears twice in some houses. Holds all the cells involved.
+    Conflict(Vec<(usize, usize)>),
+    /// An empty cell sees all the 9 digits.
+    NoCandidate { r: usize, c: usize },
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PuzzleError::OutOfRange { r, c, value } => {
+                write!(
+                    f,
+                    "R{}C{} holds {}, which is not in 0 ~ 9",
+                    r + 1,
+                    c + 1,
+                    value
+                )
+            }
+            PuzzleError::Conflict(cells) => {
+                write!(f, "conflicting digits in")?;
+                for (r, c) in cells {
+                    write!(f, " R{}C{}", r + 1, c + 1)?;
+                }
+                Ok(())
+            }
+            PuzzleError::NoCandidate { r, c } => {
+                write!(f, "R{}C{} has no candidate left", r + 1, c + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+/// Check that `board` can be given to a solver:
+/// every value is in 0 ~ 9, no digit repeats in a house and every empty cell has a candidate.
+pub fn validate(board: &Grid) -> Result<(), PuzzleError> {
+    for r in 0..9 {
+        for c in 0..9 {
+            let value = board.0[r][c];
+            if !(0..=9).contains(&value) {
+                return Err(PuzzleError::OutOfRange { r, c, value });
+            }
+        }
+    }
+
+    let (valid, _, valid_cond) = judge_sudoku(board);
+    if !valid {
+        let cells = (0..81)
+            .map(|i| (i / 9, i % 9))
+            .filter(|(r, c)| !valid_cond[*r][*c])
+            .collect();
+        return Err(PuzzleError::Conflict(cells));
+    }
+
+    // seen[r][c] = 格 (r, c) 所在行、列、宫中出现过的数字
+    let mut seen = [[[false; 10]; 9]; 9];
+    for r in 0..9 {
+        for c in 0..9 {
+            let num = board.0[r][c] as usize;
+            if num == 0 {
+                continue;
+            }
+            for i in 0..9 {
+                seen[r][i][num] = true;
+                seen[i][c][num] = true;
+                let (r1, c1) = block_idx_2_coord(coord_2_block(r, c), i);
+                seen[r1][c1][num] = true;
+            }
+        }
+    }
+    match (0..81)
+        .map(|i| (i / 9, i % 9))
+        .find(|(r, c)| board.0[*r][*c] == 0 && seen[*r][*c][1..].iter().all(|v| *v))
+    {
+        Some((r, c)) => Err(PuzzleError::NoCandidate { r, c }),
+        None => Ok(()),
+    }
+}
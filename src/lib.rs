@@ -1,11 +1,19 @@
+pub mod analysis;
+pub mod batch;
+pub mod budget;
+pub mod explain;
 pub mod generator;
 pub mod grid;
+pub mod hint;
 pub mod judge;
+pub mod sat;
+pub mod se;
 pub mod solver;
 pub mod state;
 pub mod techniques;
 #[cfg(test)]
 mod test;
 pub mod utils;
+pub mod verify;
 
 pub use grid::Grid;
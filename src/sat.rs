@@ -0,0 +1,413 @@
+use std::{fmt::Display, io, time::Instant};
+
+use crate::{
+    solver::{SolutionCount, SolveStats, Solver},
+    state::{State, TrackingCandidates},
+    utils::block_idx_2_coord,
+    Grid,
+};
+
+/// The DIMACS variable meaning "cell (r, c) is filled with `num`", numbered from 1 to 729.
+pub fn var(r: usize, c: usize, num: i8) -> i32 {
+    (r * 81 + c * 9) as i32 + num as i32
+}
+
+/// The inverse of [`var`].
+pub fn cell_of_var(var: i32) -> (usize, usize, i8) {
+    let idx = var as usize - 1;
+    (idx / 81, idx / 9 % 9, (idx % 9 + 1) as i8)
+}
+
+/// A formula in conjunctive normal form. Literals use the DIMACS convention:
+/// `v` for variable `v` and `-v` for its negation.
+///
+/// [`Cnf::sudoku`] only adds the constraints of classic sudoku.
+/// Variants are encoded by adding more houses or clauses on top of it, e.g. the two
+/// diagonals of X-Sudoku with `add_house`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cnf {
+    var_cnt: usize,
+    clauses: Vec<Vec<i32>>,
+}
+
+impl Cnf {
+    pub fn new(var_cnt: usize) -> Self {
+        Self {
+            var_cnt,
+            clauses: vec![],
+        }
+    }
+
+    /// The constraints of an empty sudoku: every cell holds exactly one digit,
+    /// and every row, column and block holds every digit exactly once.
+    pub fn sudoku() -> Self {
+        let mut cnf = Self::new(729);
+        for r in 0..9 {
+            for c in 0..9 {
+                cnf.add_clause((1..=9).map(|num| var(r, c, num)));
+                for num1 in 1..=9 {
+                    for num2 in num1 + 1..=9 {
+                        cnf.add_clause([-var(r, c, num1), -var(r, c, num2)]);
+                    }
+                }
+            }
+        }
+        for i in 0..9 {
+            cnf.add_house(&(0..9).map(|j| (i, j)).collect::<Vec<_>>());
+            cnf.add_house(&(0..9).map(|j| (j, i)).collect::<Vec<_>>());
+            cnf.add_house(&(0..9).map(|j| block_idx_2_coord(i, j)).collect::<Vec<_>>());
+        }
+        cnf
+    }
+
+    pub fn var_cnt(&self) -> usize {
+        self.var_cnt
+    }
+
+    pub fn clauses(&self) -> &[Vec<i32>] {
+        &self.clauses
+    }
+
+    pub fn add_clause(&mut self, clause: impl IntoIterator<Item = i32>) {
+        let clause: Vec<i32> = clause.into_iter().collect();
+        for lit in &clause {
+            self.var_cnt = self.var_cnt.max(lit.unsigned_abs() as usize);
+        }
+        self.clauses.push(clause);
+    }
+
+    /// Require the 9 `cells` to hold every digit exactly once.
+    pub fn add_house(&mut self, cells: &[(usize, usize)]) {
+        for num in 1..=9 {
+            self.add_clause(cells.iter().map(|(r, c)| var(*r, *c, num)));
+        }
+        self.add_all_different(cells);
+    }
+
+    /// Require `cells` to hold pairwise different digits.
+    pub fn add_all_different(&mut self, cells: &[(usize, usize)]) {
+        for (i, (r1, c1)) in cells.iter().enumerate() {
+            for (r2, c2) in &cells[i + 1..] {
+                for num in 1..=9 {
+                    self.add_clause([-var(*r1, *c1, num), -var(*r2, *c2, num)]);
+                }
+            }
+        }
+    }
+
+    pub fn write_dimacs(&self, w: &mut impl io::Write) -> io::Result<()> {
+        writeln!(w, "p cnf {} {}", self.var_cnt, self.clauses.len())?;
+        for clause in &self.clauses {
+            for lit in clause {
+                write!(w, "{} ", lit)?;
+            }
+            writeln!(w, "0")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Cnf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buf = vec![];
+        self.write_dimacs(&mut buf).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", String::from_utf8_lossy(&buf))
+    }
+}
+
+impl From<Grid> for Cnf {
+    fn from(puzzle: Grid) -> Self {
+        let mut cnf = Self::sudoku();
+        for r in 0..9 {
+            for c in 0..9 {
+                match puzzle.0[r][c] {
+                    0 => {}
+                    num @ 1..=9 => cnf.add_clause([var(r, c, num)]),
+                    // 1 ~ 9 以外的数没有对应的变量，加入空子句使公式不可满足
+                    _ => cnf.add_clause([]),
+                }
+            }
+        }
+        cnf
+    }
+}
+
+/// Encode the filled cells of `state` together with the candidates already eliminated.
+impl<T: State + TrackingCandidates> From<&T> for Cnf {
+    fn from(state: &T) -> Self {
+        let mut cnf = Self::from(state.grid());
+        for r in 0..9 {
+            for c in 0..9 {
+                if !state.is_cell_empty(r, c) {
+                    continue;
+                }
+                for num in 1..=9 {
+                    if !state.is_candidate_of(r, c, num) {
+                        cnf.add_clause([-var(r, c, num)]);
+                    }
+                }
+            }
+        }
+        cnf
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SatStats {
+    pub decisions: u64,
+    pub conflicts: u64,
+    pub propagations: u64,
+}
+
+/// A DPLL solver with unit propagation on two watched literals.
+/// It branches on the shortest unsatisfied clause of length more than 2,
+/// which for sudoku means the cell or house with the fewest options.
+///
+/// Solutions are counted as the leaves of the search tree, which is exact
+/// as long as every variable is determined by the others, as in sudoku.
+pub struct SatSolver {
+    cnf: Cnf,
+    clauses: Vec<Vec<i32>>,
+    // 变量取值：0 未赋值，1 真，-1 假
+    values: Vec<i8>,
+    // watches[lit_idx(lit)] = 监视文字 lit 的子句
+    watches: Vec<Vec<usize>>,
+    trail: Vec<i32>,
+    // trail 中尚未传播的第一个文字
+    propagated: usize,
+    model: Vec<i8>,
+    solution_cnt: u64,
+    stats: SatStats,
+    solve_stats: SolveStats,
+    depth: u32,
+}
+
+fn lit_idx(lit: i32) -> usize {
+    (lit.unsigned_abs() as usize) * 2 + (lit < 0) as usize
+}
+
+impl SatSolver {
+    pub fn sat_stats(&self) -> SatStats {
+        self.stats
+    }
+
+    /// The assignment of every variable in the last model found, indexed from 1.
+    pub fn model(&self) -> Option<Vec<bool>> {
+        (self.solution_cnt > 0).then(|| self.model.iter().map(|v| *v > 0).collect())
+    }
+
+    fn value(&self, lit: i32) -> i8 {
+        let v = self.values[lit.unsigned_abs() as usize];
+        if lit < 0 {
+            -v
+        } else {
+            v
+        }
+    }
+
+    fn assign(&mut self, lit: i32) {
+        self.values[lit.unsigned_abs() as usize] = if lit < 0 { -1 } else { 1 };
+        self.trail.push(lit);
+    }
+
+    fn undo(&mut self, trail_len: usize) {
+        while self.trail.len() > trail_len {
+            let lit = self.trail.pop().unwrap();
+            self.values[lit.unsigned_abs() as usize] = 0;
+        }
+        self.propagated = trail_len;
+    }
+
+    // 重置搜索状态；公式中有空子句或矛盾的单元子句时返回 false
+    fn init_search(&mut self) -> bool {
+        let var_cnt = self.cnf.var_cnt;
+        self.clauses.clear();
+        self.values = vec![0; var_cnt + 1];
+        self.watches = vec![vec![]; (var_cnt + 1) * 2];
+        self.trail.clear();
+        self.propagated = 0;
+        self.solution_cnt = 0;
+        self.stats = SatStats::default();
+        self.solve_stats = SolveStats::default();
+        self.depth = 0;
+        for i in 0..self.cnf.clauses.len() {
+            let clause = self.cnf.clauses[i].clone();
+            match clause.len() {
+                0 => return false,
+                1 => match self.value(clause[0]) {
+                    -1 => return false,
+                    0 => self.assign(clause[0]),
+                    _ => {}
+                },
+                _ => {
+                    self.watches[lit_idx(clause[0])].push(self.clauses.len());
+                    self.watches[lit_idx(clause[1])].push(self.clauses.len());
+                    self.clauses.push(clause);
+                }
+            }
+        }
+        true
+    }
+
+    // 单元传播，出现冲突时返回 false
+    fn propagate(&mut self) -> bool {
+        while self.propagated < self.trail.len() {
+            let false_lit = -self.trail[self.propagated];
+            self.propagated += 1;
+            self.stats.propagations += 1;
+
+            let watching = std::mem::take(&mut self.watches[lit_idx(false_lit)]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = false;
+            for (i, ci) in watching.iter().enumerate() {
+                if conflict {
+                    kept.extend_from_slice(&watching[i..]);
+                    break;
+                }
+                let ci = *ci;
+                // 保证 clauses[ci][1] 为刚变为假的文字
+                if self.clauses[ci][0] == false_lit {
+                    self.clauses[ci].swap(0, 1);
+                }
+                let first = self.clauses[ci][0];
+                if self.value(first) == 1 {
+                    kept.push(ci);
+                    continue;
+                }
+                // 寻找新的监视文字
+                if let Some(k) =
+                    (2..self.clauses[ci].len()).find(|k| self.value(self.clauses[ci][*k]) != -1)
+                {
+                    self.clauses[ci].swap(1, k);
+                    self.watches[lit_idx(self.clauses[ci][1])].push(ci);
+                    continue;
+                }
+                kept.push(ci);
+                if self.value(first) == -1 {
+                    conflict = true;
+                } else {
+                    self.assign(first);
+                }
+            }
+            self.watches[lit_idx(false_lit)] = kept;
+            if conflict {
+                return false;
+            }
+        }
+        true
+    }
+
+    // 选择未满足的、未赋值文字最少的长子句，返回其第一个未赋值文字
+    fn pick_branch(&self) -> Option<i32> {
+        let mut best = None;
+        let mut best_cnt = usize::MAX;
+        let mut fallback = None;
+        for clause in &self.clauses {
+            if clause.iter().any(|lit| self.value(*lit) == 1) {
+                continue;
+            }
+            let mut unassigned = clause.iter().filter(|lit| self.value(**lit) == 0);
+            let Some(lit) = unassigned.next() else {
+                continue;
+            };
+            if clause.len() <= 2 {
+                fallback.get_or_insert(*lit);
+                continue;
+            }
+            let cnt = 1 + unassigned.count();
+            if cnt < best_cnt {
+                best = Some(*lit);
+                best_cnt = cnt;
+            }
+        }
+        best.or(fallback)
+    }
+
+    fn search(&mut self, solution_cnt_needed: u64) -> bool {
+        self.solve_stats.nodes += 1;
+        self.solve_stats.max_depth = self.solve_stats.max_depth.max(self.depth);
+        if !self.propagate() {
+            self.stats.conflicts += 1;
+            return false;
+        }
+        let Some(lit) = self.pick_branch() else {
+            self.solution_cnt += 1;
+            self.model.clone_from(&self.values);
+            return solution_cnt_needed <= self.solution_cnt;
+        };
+
+        self.stats.decisions += 1;
+        self.solve_stats.guesses += 1;
+        self.depth += 1;
+        for lit in [lit, -lit] {
+            let trail_len = self.trail.len();
+            self.assign(lit);
+            if self.search(solution_cnt_needed) {
+                return true;
+            }
+            self.undo(trail_len);
+        }
+        self.depth -= 1;
+        false
+    }
+
+    fn run(&mut self, solution_cnt_needed: u64) {
+        let start = Instant::now();
+        if self.init_search() {
+            self.search(solution_cnt_needed);
+        }
+        self.solve_stats.elapsed = start.elapsed();
+    }
+}
+
+impl From<Cnf> for SatSolver {
+    fn from(cnf: Cnf) -> Self {
+        Self {
+            cnf,
+            clauses: vec![],
+            values: vec![],
+            watches: vec![],
+            trail: vec![],
+            propagated: 0,
+            model: vec![],
+            solution_cnt: 0,
+            stats: SatStats::default(),
+            solve_stats: SolveStats::default(),
+            depth: 0,
+        }
+    }
+}
+
+impl From<Grid> for SatSolver {
+    fn from(puzzle: Grid) -> Self {
+        Self::from(Cnf::from(puzzle))
+    }
+}
+
+impl Solver for SatSolver {
+    fn any_solution(&mut self) -> Option<Grid> {
+        self.run(1);
+        self.last_solution()
+    }
+
+    fn count_solutions(&mut self, limit: u64) -> SolutionCount {
+        self.run(limit);
+        SolutionCount::capped(self.solution_cnt, limit)
+    }
+
+    fn stats(&self) -> Option<&SolveStats> {
+        Some(&self.solve_stats)
+    }
+
+    fn last_solution(&self) -> Option<Grid> {
+        let model = self.model()?;
+        let mut solution = Grid([[0; 9]; 9]);
+        for v in 1..=729 {
+            if model[v as usize] {
+                let (r, c, num) = cell_of_var(v);
+                solution.0[r][c] = num;
+            }
+        }
+        Some(solution)
+    }
+}
@@ -0,0 +1,249 @@
+use std::{array::from_fn, fmt::Display};
+
+use crate::{
+    state::{full_state::FullState, CandidatesSettable, State, TrackingCandidates},
+    techniques::{Step, TechniqueId},
+    utils::block_idx_2_coord,
+    Grid,
+};
+
+/// A technique as Sudoku Explainer rates it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SETechnique {
+    Basic(TechniqueId),
+    /// The eliminations of the technique leave a hidden single, and the step is
+    /// that placement alone.
+    Direct(TechniqueId),
+}
+
+impl Display for SETechnique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SETechnique::Basic(id) => write!(f, "{}", id),
+            SETechnique::Direct(id) => write!(f, "Direct {}", id),
+        }
+    }
+}
+
+/// The techniques of the Sudoku Explainer hierarchy known to this crate,
+/// each with the lowest SE value it can be rated, from the easiest to the hardest.
+pub const SE_TECHNIQUES: [(SETechnique, f32); 15] = [
+    (SETechnique::Basic(TechniqueId::HiddenSingleBlock), 1.2),
+    (SETechnique::Basic(TechniqueId::HiddenSingleRow), 1.5),
+    (SETechnique::Basic(TechniqueId::HiddenSingleColumn), 1.5),
+    (SETechnique::Direct(TechniqueId::Pointing), 1.7),
+    (SETechnique::Direct(TechniqueId::Claiming), 1.9),
+    (SETechnique::Direct(TechniqueId::HiddenPair), 2.0),
+    (SETechnique::Basic(TechniqueId::NakedSingle), 2.3),
+    (SETechnique::Basic(TechniqueId::Pointing), 2.6),
+    (SETechnique::Basic(TechniqueId::Claiming), 2.8),
+    (SETechnique::Basic(TechniqueId::NakedPair), 3.0),
+    (SETechnique::Basic(TechniqueId::XWing), 3.2),
+    (SETechnique::Basic(TechniqueId::HiddenPair), 3.4),
+    (SETechnique::Basic(TechniqueId::NakedSubset), 3.6),
+    (SETechnique::Basic(TechniqueId::Swordfish), 3.8),
+    (SETechnique::Basic(TechniqueId::Jellyfish), 5.2),
+];
+
+/// A rating on the Sudoku Explainer scale.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SERating {
+    /// The hardest step of the whole solving path.
+    pub er: f32,
+    /// The hardest step up to the first placement.
+    pub ep: f32,
+    /// The first step.
+    pub ed: f32,
+}
+
+impl Display for SERating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1}/{:.1}/{:.1}", self.er, self.ep, self.ed)
+    }
+}
+
+/// Rates puzzles the way Sudoku Explainer does: always apply the easiest step available,
+/// and rate the puzzle by the SE values of the steps on the way.
+///
+/// Puzzles needing a technique beyond [`SE_TECHNIQUES`] cannot be rated.
+///
+/// ```
+/// use sudoku::{generator::random_sudoku_puzzle_easy, se::SERater};
+///
+/// let mut rater = SERater::from(random_sudoku_puzzle_easy());
+/// if let Some(rating) = rater.rate() {
+///     assert!(rating.ed <= rating.ep && rating.ep <= rating.er);
+///     println!("ER/EP/ED: {}", rating);
+/// }
+/// ```
+pub struct SERater {
+    puzzle: Grid,
+    path: Vec<(SETechnique, f32)>,
+}
+
+impl SERater {
+    /// Solve the puzzle step by step. Return `None` if it gets stuck before the grid is full.
+    pub fn rate(&mut self) -> Option<SERating> {
+        self.path.clear();
+        let mut state = FullState::from(self.puzzle);
+        let mut rating = SERating {
+            er: 0.0,
+            ep: 0.0,
+            ed: 0.0,
+        };
+        let mut placed = false;
+        while state.grid().0.iter().flatten().any(|v| *v == 0) {
+            let (technique, step, score) = easiest_step(&state)?;
+            if self.path.is_empty() {
+                rating.ed = score;
+            }
+            if !placed {
+                rating.ep = rating.ep.max(score);
+            }
+            rating.er = rating.er.max(score);
+            placed |= !step.placements().is_empty();
+            self.path.push((technique, score));
+
+            step.apply(&mut state);
+        }
+        Some(rating)
+    }
+
+    /// The techniques applied by the last `rate`, with their SE values.
+    pub fn path(&self) -> &[(SETechnique, f32)] {
+        &self.path
+    }
+}
+
+impl From<Grid> for SERater {
+    fn from(puzzle: Grid) -> Self {
+        Self {
+            puzzle,
+            path: vec![],
+        }
+    }
+}
+
+fn easiest_step(state: &FullState) -> Option<(SETechnique, Step, f32)> {
+    let mut easiest: Option<(SETechnique, Step, f32)> = None;
+    for (technique, min_score) in SE_TECHNIQUES {
+        // 之后的技巧不可能更简单
+        if easiest
+            .as_ref()
+            .is_some_and(|(_, _, score)| *score <= min_score)
+        {
+            break;
+        }
+        let found = match technique {
+            SETechnique::Basic(id) => id.analyze(state).map(|d| (d.step, d.score)),
+            SETechnique::Direct(id) => direct_step(state, id).map(|step| (step, min_score)),
+        };
+        if let Some((step, score)) = found {
+            if easiest.as_ref().is_none_or(|(_, _, s)| score < *s) {
+                easiest = Some((technique, step, score));
+            }
+        }
+    }
+    easiest
+}
+
+// technique 的某一步删数后出现的 hidden single，只填这一个数
+fn direct_step(state: &FullState, technique: TechniqueId) -> Option<Step> {
+    let rows: Vec<House> = (0..9).map(|r| from_fn(|c| (r, c))).collect();
+    let cols: Vec<House> = (0..9).map(|c| from_fn(|r| (r, c))).collect();
+    let blocks: Vec<House> = (0..9)
+        .map(|b| from_fn(|bidx| block_idx_2_coord(b, bidx)))
+        .collect();
+    let lines = [rows, cols].concat();
+    let eliminations = match technique {
+        TechniqueId::Pointing => locked_eliminations(state, &blocks, &lines),
+        TechniqueId::Claiming => locked_eliminations(state, &lines, &blocks),
+        TechniqueId::HiddenPair => hidden_pair_eliminations(state, &[lines, blocks].concat()),
+        _ => vec![],
+    };
+    eliminations.into_iter().find_map(|removes| {
+        let mut state = state.clone();
+        for (r, c, num) in removes {
+            state.remove_candidate_of_cell(r, c, num);
+        }
+        TechniqueId::HiddenSingle
+            .analyze(&state)
+            .map(|single| single.step)
+    })
+}
+
+// 一个区域的九个格子
+type House = [(usize, usize); 9];
+
+fn is_candidate(state: &FullState, (r, c): (usize, usize), num: i8) -> bool {
+    state.is_cell_empty(r, c) && state.is_candidate_of(r, c, num)
+}
+
+// base 中 num 的候选格都在 cover 里时，删去 cover 里其余格子的 num
+fn locked_eliminations(
+    state: &FullState,
+    bases: &[House],
+    covers: &[House],
+) -> Vec<Vec<(usize, usize, i8)>> {
+    let mut found = vec![];
+    for base in bases {
+        for cover in covers {
+            for num in 1..=9 {
+                let in_base: Vec<_> = base
+                    .iter()
+                    .filter(|cell| is_candidate(state, **cell, num))
+                    .collect();
+                if in_base.is_empty() || !in_base.iter().all(|cell| cover.contains(cell)) {
+                    continue;
+                }
+                let removes: Vec<_> = cover
+                    .iter()
+                    .filter(|cell| !base.contains(cell) && is_candidate(state, **cell, num))
+                    .map(|(r, c)| (*r, *c, num))
+                    .collect();
+                if !removes.is_empty() {
+                    found.push(removes);
+                }
+            }
+        }
+    }
+    found
+}
+
+// 两个数在区域里都只能填在同样的两格时，删去这两格的其他候选数
+fn hidden_pair_eliminations(state: &FullState, houses: &[House]) -> Vec<Vec<(usize, usize, i8)>> {
+    let mut found = vec![];
+    for house in houses {
+        let cells_of = |num: i8| -> Vec<(usize, usize)> {
+            house
+                .iter()
+                .filter(|cell| is_candidate(state, **cell, num))
+                .copied()
+                .collect()
+        };
+        for num1 in 1..=9 {
+            let cells = cells_of(num1);
+            if cells.len() != 2 {
+                continue;
+            }
+            for num2 in num1 + 1..=9 {
+                if cells_of(num2) != cells {
+                    continue;
+                }
+                let removes: Vec<_> = cells
+                    .iter()
+                    .flat_map(|&(r, c)| {
+                        (1..=9)
+                            .filter(move |num| *num != num1 && *num != num2)
+                            .filter(move |num| state.is_candidate_of(r, c, *num))
+                            .map(move |num| (r, c, num))
+                    })
+                    .collect();
+                if !removes.is_empty() {
+                    found.push(removes);
+                }
+            }
+        }
+    }
+    found
+}
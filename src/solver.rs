@@ -1,11 +1,107 @@
-use crate::Grid;
+use std::{collections::BTreeMap, time::Duration};
 
-use super::state::State;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    budget::{Budget, Interrupted},
+    techniques::TechniqueId,
+    Grid,
+};
+
+use super::state::{Fillable, State, TrackingCandidates};
+
+/// The result of counting solutions with an upper limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolutionCount {
+    /// The search finished and found exactly this many solutions.
+    Exact(u64),
+    /// The search stopped after finding `limit` solutions.
+    AtLeast(u64),
+}
+
+impl SolutionCount {
+    pub(crate) fn capped(solution_cnt: u64, limit: u64) -> Self {
+        if solution_cnt >= limit {
+            Self::AtLeast(limit)
+        } else {
+            Self::Exact(solution_cnt)
+        }
+    }
+}
+
+/// What the last search of a solver went through.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolveStats {
+    /// Calls of the recursive search.
+    pub nodes: u64,
+    /// Branching points with more than one option to try.
+    pub guesses: u64,
+    /// The deepest level of the backtracking search, never more than 81.
+    /// Only the guesses of `AdvancedSolver` go deeper, not the steps of its techniques.
+    pub max_depth: u32,
+    /// How many times each technique was applied, only counted by `AdvancedSolver`.
+    pub technique_counts: BTreeMap<TechniqueId, u64>,
+    pub elapsed: Duration,
+}
 
 pub trait Solver {
     fn any_solution(&mut self) -> Option<Grid>;
-    fn solution_cnt(&mut self) -> u32;
-    fn have_unique_solution(&mut self) -> bool;
+
+    /// Count the solutions, stopping as soon as `limit` of them are found.
+    fn count_solutions(&mut self, limit: u64) -> SolutionCount;
+
+    /// Count all the solutions, saturating at `u32::MAX`.
+    /// Prefer `count_solutions` for puzzles which may have lots of solutions.
+    fn solution_cnt(&mut self) -> u32 {
+        match self.count_solutions(u32::MAX as u64) {
+            SolutionCount::Exact(cnt) => cnt as u32,
+            SolutionCount::AtLeast(_) => u32::MAX,
+        }
+    }
+
+    fn have_unique_solution(&mut self) -> bool {
+        self.count_solutions(2) == SolutionCount::Exact(1)
+    }
+
+    /// Like `any_solution`, but gives up once `budget` runs out.
+    /// Solvers which cannot be interrupted only check the budget before the search.
+    fn try_any_solution(&mut self, budget: &Budget) -> Result<Option<Grid>, Interrupted> {
+        budget.check()?;
+        Ok(self.any_solution())
+    }
+
+    /// Like `count_solutions`, but gives up once `budget` runs out.
+    /// Solvers which cannot be interrupted only check the budget before the search.
+    fn try_count_solutions(
+        &mut self,
+        limit: u64,
+        budget: &Budget,
+    ) -> Result<SolutionCount, Interrupted> {
+        budget.check()?;
+        Ok(self.count_solutions(limit))
+    }
+
+    /// The statistics of the last call of `any_solution` or `count_solutions`,
+    /// if the solver keeps them.
+    fn stats(&self) -> Option<&SolveStats> {
+        None
+    }
+
+    /// The last solution found by the last call of `any_solution` or `count_solutions`,
+    /// if the solver keeps it.
+    fn last_solution(&self) -> Option<Grid> {
+        None
+    }
+}
+
+/// A solver whose search makes random choices, e.g. to produce random full grids.
+pub trait RandomizedSolver: Solver + Sized {
+    /// Take all the randomness of the following searches from `rng`.
+    fn with_rng(self, rng: &mut impl Rng) -> Self;
+
+    fn with_seed(self, seed: u64) -> Self {
+        self.with_rng(&mut StdRng::seed_from_u64(seed))
+    }
 }
 
 pub trait Grader<T: PartialOrd> {
@@ -27,6 +123,75 @@ fn next_blank(mut row: usize, mut col: usize, state: &impl State) -> Option<(usi
     Some((row, col))
 }
 
+/// A lazy iterator over all solutions of a puzzle.
+///
+/// Blank cells are filled in row-major order and digits are tried in ascending order,
+/// so the solutions come out in ascending order when read row by row.
+pub struct Solutions<T>
+where
+    T: State + Fillable + TrackingCandidates,
+{
+    state: T,
+    // 搜索栈：(r, c, 当前填入的数)，0 表示还未填数
+    stack: Vec<(usize, usize, i8)>,
+    // 是否需要继续寻找下一个空格
+    descending: bool,
+}
+
+impl<T> Solutions<T>
+where
+    T: State + Fillable + TrackingCandidates,
+{
+    fn new(state: T) -> Self {
+        Self {
+            state,
+            stack: Vec::with_capacity(81),
+            descending: true,
+        }
+    }
+}
+
+impl<T> Iterator for Solutions<T>
+where
+    T: State + Fillable + TrackingCandidates,
+{
+    type Item = Grid;
+
+    fn next(&mut self) -> Option<Grid> {
+        loop {
+            if self.descending {
+                let (r, c) = self.stack.last().map_or((0, 0), |(r, c, _)| (*r, *c));
+                match next_blank(r, c, &self.state) {
+                    Some((r, c)) => self.stack.push((r, c, 0)),
+                    None => {
+                        self.descending = false;
+                        return Some(self.state.grid());
+                    }
+                }
+            }
+
+            // 在栈顶的格子中换下一个候选数，没有候选数时回溯
+            let (r, c, num) = *self.stack.last()?;
+            if num > 0 {
+                self.state.unfill_cell(r, c);
+            }
+            match (num + 1..=9).find(|num| self.state.is_candidate_of(r, c, *num)) {
+                Some(num) => {
+                    self.state.fill_cell(r, c, num);
+                    self.stack.last_mut().unwrap().2 = num;
+                    self.descending = true;
+                }
+                None => {
+                    self.stack.pop();
+                    self.descending = false;
+                }
+            }
+        }
+    }
+}
+
 pub mod advanced;
+pub mod bitboard;
+pub mod dlx;
 // pub mod basic;
 pub mod stochastic;
@@ -1,20 +1,135 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Instant,
+};
+
 use crate::{
+    budget::{Budget, Interrupted},
+    judge::{validate, PuzzleError},
     state::{
         full_state::FullState, CandidatesSettable, Fillable, State, TrackingCandidateCountOfCell,
         TrackingCandidates, TrackingCellCountOfCandidate,
     },
-    techniques::{
-        fish::{Jellyfish, Swordfish, XWing},
-        hidden_subsets::HiddenPair,
-        locked_candidates::{Claiming, Pointing},
-        naked_subsets::{NakedPair, NakedSubset},
-        singles::{HiddenSingle, NakedSingle},
-        Direct, DirectOption, ReducingCandidates, ReducingCandidatesOption,
-    },
+    techniques::{Deduction, DirectOption, Step, TechniqueCategory, TechniqueId},
     Grid,
 };
 
-use super::{Grader, Solver};
+use super::{Grader, SolutionCount, Solutions, SolveStats, Solver};
+
+/// Which techniques `AdvancedSolver` uses, in which order they are tried,
+/// and how much each of them (and guessing) adds to the difficulty score.
+///
+/// ```
+/// use sudoku::{solver::advanced::SolverConfig, techniques::TechniqueId};
+///
+/// // a beginner mode without any fish
+/// let config = SolverConfig::default()
+///     .without(TechniqueId::XWing)
+///     .without(TechniqueId::Swordfish)
+///     .without(TechniqueId::Jellyfish);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SolverConfig {
+    techniques: Vec<TechniqueId>,
+    scores: HashMap<TechniqueId, f32>,
+    guess_score: f32,
+    policy: StepPolicy,
+    record_path: bool,
+}
+
+/// How `AdvancedSolver` chooses the next step among the enabled techniques.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepPolicy {
+    /// Apply the first technique in the configured order which makes progress.
+    #[default]
+    FirstApplicable,
+    /// Find every step each technique can take and apply the one with the lowest score,
+    /// so the rating does not depend on the order of the techniques.
+    Easiest,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            techniques: TechniqueId::DEFAULT.to_vec(),
+            scores: HashMap::new(),
+            guess_score: 8.0,
+            policy: StepPolicy::default(),
+            record_path: false,
+        }
+    }
+}
+
+impl SolverConfig {
+    /// Enable exactly `techniques`, tried in the given order.
+    pub fn techniques(mut self, techniques: &[TechniqueId]) -> Self {
+        self.techniques = techniques.to_vec();
+        self
+    }
+
+    /// Disable `technique`.
+    pub fn without(mut self, technique: TechniqueId) -> Self {
+        self.techniques.retain(|id| *id != technique);
+        self
+    }
+
+    /// Use `score` for every application of `technique`
+    /// instead of the score reported by the technique itself.
+    pub fn score(mut self, technique: TechniqueId, score: f32) -> Self {
+        self.scores.insert(technique, score);
+        self
+    }
+
+    /// The score added for each guess the solver has to make.
+    pub fn guess_score(mut self, score: f32) -> Self {
+        self.guess_score = score;
+        self
+    }
+
+    /// Choose the next step with `policy`.
+    pub fn policy(mut self, policy: StepPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Keep the moves leading to the solution, for [`AdvancedSolver::path`] and
+    /// [`AdvancedSolver::report`]. Off by default, as it slows the search down.
+    pub fn record_path(mut self, record_path: bool) -> Self {
+        self.record_path = record_path;
+        self
+    }
+
+    pub fn enabled_techniques(&self) -> &[TechniqueId] {
+        &self.techniques
+    }
+
+    pub fn score_of(&self, technique: TechniqueId) -> Option<f32> {
+        self.scores.get(&technique).copied()
+    }
+}
+
+/// One move on the way to a solution: a technique, or a guess if `technique` is `None`.
+#[derive(Clone, Debug)]
+pub struct PathStep {
+    pub technique: Option<TechniqueId>,
+    pub step: Step,
+    pub score: f32,
+}
+
+/// Why a puzzle is as hard as `AdvancedSolver` rates it, following the path to the solution found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DifficultyReport {
+    /// The technique with the highest score on the path, `None` if no technique was used.
+    pub hardest: Option<TechniqueId>,
+    pub steps_by_category: BTreeMap<TechniqueCategory, u64>,
+    /// Guesses on the path with more than one candidate to choose from.
+    pub guesses: u64,
+    /// For every step, how many different steps the enabled techniques could have taken instead.
+    pub branching: Vec<usize>,
+    pub clue_cnt: usize,
+    /// The same value as [`Grader::difficulty`].
+    pub score: f32,
+}
 
 pub struct AdvancedSolver<T = FullState>
 where
@@ -27,12 +142,21 @@ where
         + Clone,
 {
     state: T,
-    solution_cnt: u32,
+    solution: Grid,
+    solution_cnt: u64,
     tmp_state: T,
     tmp_score: f32,
     tmp_max_tech_score: f32,
     score: f32,
     max_tech_score: f32,
+    config: SolverConfig,
+    budget: Budget,
+    interrupted: Option<Interrupted>,
+    stats: SolveStats,
+    depth: u32,
+    // 当前搜索路径，以及找到最后一个解时的路径
+    trail: Vec<PathStep>,
+    path: Vec<PathStep>,
 }
 
 impl<T> AdvancedSolver<T>
@@ -45,82 +169,178 @@ where
         + TrackingCellCountOfCandidate
         + Clone,
 {
+    /// Replace the techniques, their order and their scores used by the solver.
+    pub fn with_config(mut self, config: SolverConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Like `from`, but rejects grids which break the rules, see [`validate`].
+    pub fn new(puzzle: Grid) -> Result<Self, PuzzleError> {
+        validate(&puzzle)?;
+        Ok(Self::from(puzzle))
+    }
+
+    /// Iterate over all solutions in a deterministic order, see [`Solutions`].
+    /// Unlike the other methods, it does not use any technique.
+    pub fn solutions(&self) -> Solutions<T> {
+        Solutions::new(self.tmp_state.clone())
+    }
+
+    /// Return at most `n` solutions, in the same order as `solutions`.
+    pub fn solutions_up_to(&self, n: usize) -> Vec<Grid> {
+        self.solutions().take(n).collect()
+    }
+
+    /// The moves leading to the last solution found,
+    /// empty unless the config asks to [record](SolverConfig::record_path) them.
+    pub fn path(&self) -> &[PathStep] {
+        &self.path
+    }
+
+    /// Explain the difficulty of the last solution found. Return `None` if none was found,
+    /// or if the config does not [record](SolverConfig::record_path) the path.
+    pub fn report(&self) -> Option<DifficultyReport> {
+        if self.solution_cnt == 0 || !self.config.record_path {
+            return None;
+        }
+        let mut report = DifficultyReport {
+            hardest: None,
+            steps_by_category: BTreeMap::new(),
+            guesses: 0,
+            branching: vec![],
+            clue_cnt: self
+                .tmp_state
+                .grid()
+                .0
+                .iter()
+                .flatten()
+                .filter(|v| **v > 0)
+                .count(),
+            score: self.difficulty(),
+        };
+        let mut hardest_score = f32::MIN;
+        // 沿着解的路径重走一遍，统计每一步可走的不同步骤数
+        let mut state = self.tmp_state.clone();
+        for PathStep {
+            technique,
+            step,
+            score,
+        } in &self.path
+        {
+            let mut options: Vec<Step> = vec![];
+            for deduction in self
+                .config
+                .techniques
+                .iter()
+                .flat_map(|id| id.analyze_all(&state))
+            {
+                if !options.contains(&deduction.step) {
+                    options.push(deduction.step);
+                }
+            }
+            report.branching.push(options.len());
+            match technique {
+                Some(technique) => {
+                    *report
+                        .steps_by_category
+                        .entry(technique.category())
+                        .or_default() += 1;
+                    if *score > hardest_score {
+                        report.hardest = Some(*technique);
+                        hardest_score = *score;
+                    }
+                }
+                None => {
+                    let DirectOption(r, c, _) = step.placements()[0];
+                    if state.candidate_cnt_of_cell(r, c) > 1 {
+                        report.guesses += 1;
+                    }
+                }
+            }
+            step.apply(&mut state);
+        }
+        Some(report)
+    }
+
     fn init_search(&mut self) {
         self.solution_cnt = 0;
+        self.interrupted = None;
+        self.stats = SolveStats::default();
+        self.depth = 0;
+        self.trail.clear();
+        self.path.clear();
         self.state = self.tmp_state.clone();
     }
 
-    fn search(&mut self, solution_cnt_needed: u32) -> bool {
+    // 按配置的策略选出下一步，分数已按配置调整
+    fn next_step(&self) -> Option<(TechniqueId, Step, f32)> {
+        let scored = |technique: TechniqueId, Deduction { step, score, .. }| {
+            (
+                technique,
+                step,
+                self.config.score_of(technique).unwrap_or(score),
+            )
+        };
+        let techniques = self.config.techniques.iter().copied();
+        match self.config.policy {
+            StepPolicy::FirstApplicable => techniques
+                .filter_map(|technique| {
+                    technique
+                        .analyze(&self.state)
+                        .map(|deduction| scored(technique, deduction))
+                })
+                .next(),
+            StepPolicy::Easiest => techniques
+                .flat_map(|technique| {
+                    technique
+                        .analyze_all(&self.state)
+                        .into_iter()
+                        .map(move |deduction| scored(technique, deduction))
+                })
+                .min_by(|d1, d2| d1.2.total_cmp(&d2.2)),
+        }
+    }
+
+    fn search(&mut self, solution_cnt_needed: u64) -> bool {
+        if let Err(interrupted) = self.budget.check() {
+            self.interrupted = Some(interrupted);
+            return true;
+        }
+        self.stats.nodes += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
         if self.state.grid().0.iter().flatten().all(|v| *v > 0) {
             self.solution_cnt += 1;
+            self.solution = self.state.grid();
             self.score = self.tmp_score;
             self.max_tech_score = self.tmp_max_tech_score;
+            if self.config.record_path {
+                self.path = self.trail.clone();
+            }
             return solution_cnt_needed <= self.solution_cnt;
         }
 
-        let direct_techniques: [&mut dyn Direct<T>; 2] =
-            [&mut HiddenSingle::default(), &mut NakedSingle::default()];
-
-        for technique in direct_techniques {
-            technique.analyze(&self.state);
-            if technique.appliable() {
-                let DirectOption(r, c, num) = technique.option().unwrap();
-                // println!("{}", DirectOption(r, c, num));
-                let score = technique.score().unwrap();
-                self.state.fill_cell(r, c, num);
-                self.tmp_score += score;
-                if self.search(solution_cnt_needed) {
-                    return true;
-                }
-                self.state.unfill_cell(r, c);
-                self.tmp_score -= score;
-                return false;
+        if let Some((technique, step, score)) = self.next_step() {
+            *self.stats.technique_counts.entry(technique).or_default() += 1;
+            let removed = step.apply(&mut self.state);
+            self.tmp_score += score;
+            let tmp_max_tech_score = self.tmp_max_tech_score;
+            self.tmp_max_tech_score = score.max(self.tmp_max_tech_score);
+            if self.config.record_path {
+                self.trail.push(PathStep {
+                    technique: Some(technique),
+                    step: step.clone(),
+                    score,
+                });
             }
-        }
-
-        let reducing_techniques: [&mut dyn ReducingCandidates<T>; 8] = [
-            &mut Pointing::default(),
-            &mut Claiming::default(),
-            &mut NakedPair::default(),
-            &mut XWing::default(),
-            &mut HiddenPair::default(),
-            &mut NakedSubset::default(),
-            &mut Swordfish::default(),
-            &mut Jellyfish::default(),
-        ];
-        // TODO: Fish, Unique Rectangle
-
-        for technique in reducing_techniques {
-            technique.analyze(&self.state);
-            if technique.appliable() {
-                let ReducingCandidatesOption(rems) = technique.option().unwrap();
-                // println!("{}", ReducingCandidatesOption(rems.clone()));
-                let score = technique.score().unwrap();
-                for (cells, nums) in &rems {
-                    for (r, c) in cells {
-                        for num in nums {
-                            // assert!(self.state.is_cell_empty(*r, *c));
-                            self.state.remove_candidate_of_cell(*r, *c, *num);
-                        }
-                    }
-                }
-                self.tmp_score += score;
-                let tmp_max_tech_score = self.tmp_max_tech_score;
-                self.tmp_max_tech_score = score.max(self.tmp_max_tech_score);
-                if self.search(solution_cnt_needed) {
-                    return true;
-                }
-                for (cells, nums) in &rems {
-                    for (r, c) in cells {
-                        for num in nums {
-                            self.state.add_candidate_of_cell(*r, *c, *num);
-                        }
-                    }
-                }
-                self.tmp_score -= score;
-                self.tmp_max_tech_score = tmp_max_tech_score;
-                return false;
+            if self.search(solution_cnt_needed) {
+                return true;
             }
+            self.trail.pop();
+            step.undo(&mut self.state, removed);
+            self.tmp_score -= score;
+            self.tmp_max_tech_score = tmp_max_tech_score;
+            return false;
         }
 
         // 实在不行，找一个候选数字最少的空随便猜一个填上
@@ -141,16 +361,30 @@ where
             }
         }
         let (r, c) = grid;
+        let guess_score = self.config.guess_score;
+        if self.state.candidate_cnt_of_cell(r, c) > 1 {
+            self.stats.guesses += 1;
+        }
         for num in 1..=9 {
             if self.state.is_candidate_of(r, c, num) {
                 self.state.fill_cell(r, c, num);
-                self.tmp_score += 8.0;
+                self.tmp_score += guess_score;
                 let tmp_max_tech_score = self.tmp_max_tech_score;
-                self.tmp_max_tech_score = 8.0f32.max(self.tmp_max_tech_score);
+                self.tmp_max_tech_score = guess_score.max(self.tmp_max_tech_score);
+                self.depth += 1;
+                if self.config.record_path {
+                    self.trail.push(PathStep {
+                        technique: None,
+                        step: Step::Placement(DirectOption(r, c, num)),
+                        score: guess_score,
+                    });
+                }
                 if self.search(solution_cnt_needed) {
                     return true;
                 }
-                self.tmp_score -= 8.0;
+                self.trail.pop();
+                self.depth -= 1;
+                self.tmp_score -= guess_score;
                 self.tmp_max_tech_score = tmp_max_tech_score;
                 self.state.unfill_cell(r, c);
             }
@@ -174,12 +408,20 @@ where
         let state = T::from(puzzle);
         Self {
             state: state.clone(),
+            solution: state.grid(),
             solution_cnt: 0,
             tmp_state: state,
             tmp_score: 0.0,
             tmp_max_tech_score: 1.0,
             score: 0.0,
             max_tech_score: 0.0,
+            config: SolverConfig::default(),
+            budget: Budget::default(),
+            interrupted: None,
+            stats: SolveStats::default(),
+            depth: 0,
+            trail: vec![],
+            path: vec![],
         }
     }
 }
@@ -197,12 +439,20 @@ where
     fn from(state: T) -> Self {
         Self {
             state: state.clone(),
+            solution: state.grid(),
             solution_cnt: 0,
             tmp_state: state,
             tmp_score: 0.0,
             tmp_max_tech_score: 1.0,
             score: 0.0,
             max_tech_score: 0.0,
+            config: SolverConfig::default(),
+            budget: Budget::default(),
+            interrupted: None,
+            stats: SolveStats::default(),
+            depth: 0,
+            trail: vec![],
+            path: vec![],
         }
     }
 }
@@ -218,23 +468,51 @@ where
         + Clone,
 {
     fn any_solution(&mut self) -> Option<Grid> {
+        let start = Instant::now();
         self.init_search();
-        if self.search(1) {
-            return Some(self.state.grid());
-        }
-        None
+        let found = self.search(1);
+        self.stats.elapsed = start.elapsed();
+        found.then(|| self.state.grid())
     }
 
-    fn solution_cnt(&mut self) -> u32 {
+    fn count_solutions(&mut self, limit: u64) -> SolutionCount {
+        let start = Instant::now();
         self.init_search();
-        self.search(u32::MAX);
-        self.solution_cnt
+        self.search(limit);
+        self.stats.elapsed = start.elapsed();
+        SolutionCount::capped(self.solution_cnt, limit)
     }
 
-    fn have_unique_solution(&mut self) -> bool {
-        self.init_search();
-        self.search(2);
-        self.solution_cnt == 1
+    fn try_any_solution(&mut self, budget: &Budget) -> Result<Option<Grid>, Interrupted> {
+        self.budget = budget.clone();
+        let solution = self.any_solution();
+        self.budget = Budget::default();
+        match self.interrupted {
+            Some(interrupted) => Err(interrupted),
+            None => Ok(solution),
+        }
+    }
+
+    fn try_count_solutions(
+        &mut self,
+        limit: u64,
+        budget: &Budget,
+    ) -> Result<SolutionCount, Interrupted> {
+        self.budget = budget.clone();
+        let solution_cnt = self.count_solutions(limit);
+        self.budget = Budget::default();
+        match self.interrupted {
+            Some(interrupted) => Err(interrupted),
+            None => Ok(solution_cnt),
+        }
+    }
+
+    fn stats(&self) -> Option<&SolveStats> {
+        Some(&self.stats)
+    }
+
+    fn last_solution(&self) -> Option<Grid> {
+        (self.solution_cnt > 0).then_some(self.solution)
     }
 }
 
@@ -0,0 +1,220 @@
+use std::time::Instant;
+
+use super::{Grader, SolutionCount, SolveStats, Solver};
+
+use crate::{
+    budget::{Budget, Interrupted},
+    utils::coord_2_block,
+    Grid,
+};
+
+const ALL: u16 = 0b11_1111_1110;
+
+/// A backtracking solver keeping the digits of every row, column and block as `u16` bitmasks.
+/// It always branches on the cell with the fewest candidates and never allocates,
+/// which makes it suitable for checking the uniqueness of lots of puzzles.
+///
+/// As a `Grader`, it reports the number of guesses made during the last search,
+/// which is a rough measure of how hard the puzzle is for a computer, not for a human.
+pub struct BitboardSolver {
+    puzzle: Grid,
+    grid: [i8; 81],
+    rows: [u16; 9],
+    cols: [u16; 9],
+    blocks: [u16; 9],
+    // 空格子的下标，前 blank_cnt 个为尚未填上的格子
+    blanks: [u8; 81],
+    blank_cnt: usize,
+    solution: Grid,
+    solution_cnt: u64,
+    budget: Budget,
+    interrupted: Option<Interrupted>,
+    stats: SolveStats,
+    // 搜索开始时的空格数
+    init_blank_cnt: usize,
+}
+
+impl BitboardSolver {
+    // 返回题目是否自洽
+    fn init_search(&mut self) -> bool {
+        self.rows = [0; 9];
+        self.cols = [0; 9];
+        self.blocks = [0; 9];
+        self.blank_cnt = 0;
+        self.solution_cnt = 0;
+        self.interrupted = None;
+        self.stats = SolveStats::default();
+        for r in 0..9 {
+            for c in 0..9 {
+                let num = self.puzzle.0[r][c];
+                self.grid[r * 9 + c] = num;
+                if num == 0 {
+                    self.blanks[self.blank_cnt] = (r * 9 + c) as u8;
+                    self.blank_cnt += 1;
+                    continue;
+                }
+                if !(1..=9).contains(&num) {
+                    return false;
+                }
+                let bit = 1 << num;
+                let b = coord_2_block(r, c);
+                if (self.rows[r] | self.cols[c] | self.blocks[b]) & bit != 0 {
+                    return false;
+                }
+                self.rows[r] |= bit;
+                self.cols[c] |= bit;
+                self.blocks[b] |= bit;
+            }
+        }
+        self.init_blank_cnt = self.blank_cnt;
+        true
+    }
+
+    fn candidates(&self, idx: usize) -> u16 {
+        let (r, c) = (idx / 9, idx % 9);
+        !(self.rows[r] | self.cols[c] | self.blocks[coord_2_block(r, c)]) & ALL
+    }
+
+    fn search(&mut self, solution_cnt_needed: u64) -> bool {
+        // 每个结点都很快，每隔一段才检查一次预算
+        if self.stats.nodes.is_multiple_of(1024) {
+            if let Err(interrupted) = self.budget.check() {
+                self.interrupted = Some(interrupted);
+                return true;
+            }
+        }
+        self.stats.nodes += 1;
+        let depth = (self.init_blank_cnt - self.blank_cnt) as u32;
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        if self.blank_cnt == 0 {
+            self.solution_cnt += 1;
+            for idx in 0..81 {
+                self.solution.0[idx / 9][idx % 9] = self.grid[idx];
+            }
+            return solution_cnt_needed <= self.solution_cnt;
+        }
+
+        // 找候选数最少的空格子
+        let mut best = 0;
+        let mut best_cnt = u32::MAX;
+        for i in 0..self.blank_cnt {
+            let cnt = self.candidates(self.blanks[i] as usize).count_ones();
+            if cnt < best_cnt {
+                best = i;
+                best_cnt = cnt;
+                if cnt <= 1 {
+                    break;
+                }
+            }
+        }
+        if best_cnt == 0 {
+            return false;
+        }
+        if best_cnt > 1 {
+            self.stats.guesses += 1;
+        }
+
+        self.blank_cnt -= 1;
+        self.blanks.swap(best, self.blank_cnt);
+        let idx = self.blanks[self.blank_cnt] as usize;
+        let (r, c) = (idx / 9, idx % 9);
+        let b = coord_2_block(r, c);
+        let mut candidates = self.candidates(idx);
+        while candidates != 0 {
+            let bit = candidates & candidates.wrapping_neg();
+            candidates ^= bit;
+            self.grid[idx] = bit.trailing_zeros() as i8;
+            self.rows[r] |= bit;
+            self.cols[c] |= bit;
+            self.blocks[b] |= bit;
+
+            if self.search(solution_cnt_needed) {
+                return true;
+            }
+
+            self.rows[r] ^= bit;
+            self.cols[c] ^= bit;
+            self.blocks[b] ^= bit;
+        }
+        self.grid[idx] = 0;
+        self.blank_cnt += 1;
+
+        false
+    }
+}
+
+impl From<Grid> for BitboardSolver {
+    fn from(puzzle: Grid) -> Self {
+        Self {
+            puzzle,
+            grid: [0; 81],
+            rows: [0; 9],
+            cols: [0; 9],
+            blocks: [0; 9],
+            blanks: [0; 81],
+            blank_cnt: 0,
+            solution: puzzle,
+            solution_cnt: 0,
+            budget: Budget::default(),
+            interrupted: None,
+            stats: SolveStats::default(),
+            init_blank_cnt: 0,
+        }
+    }
+}
+
+impl Solver for BitboardSolver {
+    fn any_solution(&mut self) -> Option<Grid> {
+        let start = Instant::now();
+        let found = self.init_search() && self.search(1);
+        self.stats.elapsed = start.elapsed();
+        found.then_some(self.solution)
+    }
+
+    fn count_solutions(&mut self, limit: u64) -> SolutionCount {
+        let start = Instant::now();
+        if self.init_search() {
+            self.search(limit);
+        }
+        self.stats.elapsed = start.elapsed();
+        SolutionCount::capped(self.solution_cnt, limit)
+    }
+
+    fn try_any_solution(&mut self, budget: &Budget) -> Result<Option<Grid>, Interrupted> {
+        self.budget = budget.clone();
+        let solution = self.any_solution();
+        self.budget = Budget::default();
+        match self.interrupted {
+            Some(interrupted) => Err(interrupted),
+            None => Ok(solution),
+        }
+    }
+
+    fn try_count_solutions(
+        &mut self,
+        limit: u64,
+        budget: &Budget,
+    ) -> Result<SolutionCount, Interrupted> {
+        self.budget = budget.clone();
+        let solution_cnt = self.count_solutions(limit);
+        self.budget = Budget::default();
+        match self.interrupted {
+            Some(interrupted) => Err(interrupted),
+            None => Ok(solution_cnt),
+        }
+    }
+
+    fn stats(&self) -> Option<&SolveStats> {
+        Some(&self.stats)
+    }
+
+    fn last_solution(&self) -> Option<Grid> {
+        (self.solution_cnt > 0).then_some(self.solution)
+    }
+}
+
+impl Grader<f32> for BitboardSolver {
+    fn difficulty(&self) -> f32 {
+        self.stats.guesses as f32
+    }
+}
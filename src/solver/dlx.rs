@@ -0,0 +1,252 @@
+use std::time::Instant;
+
+use super::{SolutionCount, SolveStats, Solver};
+
+use crate::{utils::coord_2_block, Grid};
+
+// 精确覆盖矩阵的列：
+// 1 ~ 81 格 (r, c) 已填；82 ~ 162 第 r 行有数 num；
+// 163 ~ 243 第 c 列有数 num；244 ~ 324 第 b 宫有数 num
+const COLUMN_CNT: usize = 324;
+const ROOT: usize = 0;
+
+fn columns_of(r: usize, c: usize, num: usize) -> [usize; 4] {
+    [
+        1 + r * 9 + c,
+        1 + 81 + r * 9 + num - 1,
+        1 + 162 + c * 9 + num - 1,
+        1 + 243 + coord_2_block(r, c) * 9 + num - 1,
+    ]
+}
+
+// Dancing Links：节点 0 为根，1 ~ 324 为列头，其余为矩阵中的 1
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    // row[node] = 节点所在行代表的 (r, c, num)
+    row: Vec<(usize, usize, i8)>,
+    size: Vec<usize>,
+}
+
+impl Links {
+    fn new() -> Self {
+        let node_cnt = 1 + COLUMN_CNT + 729 * 4;
+        let mut links = Self {
+            left: Vec::with_capacity(node_cnt),
+            right: Vec::with_capacity(node_cnt),
+            up: Vec::with_capacity(node_cnt),
+            down: Vec::with_capacity(node_cnt),
+            column: Vec::with_capacity(node_cnt),
+            row: Vec::with_capacity(node_cnt),
+            size: vec![0; 1 + COLUMN_CNT],
+        };
+        for i in 0..=COLUMN_CNT {
+            links.left.push(if i == 0 { COLUMN_CNT } else { i - 1 });
+            links.right.push(if i == COLUMN_CNT { 0 } else { i + 1 });
+            links.up.push(i);
+            links.down.push(i);
+            links.column.push(i);
+            links.row.push((0, 0, 0));
+        }
+        for r in 0..9 {
+            for c in 0..9 {
+                for num in 1..=9 {
+                    links.add_row((r, c, num as i8), columns_of(r, c, num));
+                }
+            }
+        }
+        links
+    }
+
+    fn add_row(&mut self, row: (usize, usize, i8), columns: [usize; 4]) {
+        let first = self.left.len();
+        for (i, col) in columns.into_iter().enumerate() {
+            let node = first + i;
+            self.left.push(if i == 0 { first + 3 } else { node - 1 });
+            self.right.push(if i == 3 { first } else { node + 1 });
+            self.up.push(self.up[col]);
+            self.down.push(col);
+            let last = self.up[col];
+            self.down[last] = node;
+            self.up[col] = node;
+            self.column.push(col);
+            self.row.push(row);
+            self.size[col] += 1;
+        }
+    }
+
+    fn cover(&mut self, col: usize) {
+        self.right[self.left[col]] = self.right[col];
+        self.left[self.right[col]] = self.left[col];
+        let mut i = self.down[col];
+        while i != col {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, col: usize) {
+        let mut i = self.up[col];
+        while i != col {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        self.right[self.left[col]] = col;
+        self.left[self.right[col]] = col;
+    }
+
+    fn is_covered(&self, col: usize) -> bool {
+        self.left[self.right[col]] != col
+    }
+}
+
+/// A solver using Knuth's Algorithm X with Dancing Links
+/// on the 324-column exact cover matrix of sudoku.
+pub struct DlxSolver {
+    puzzle: Grid,
+    links: Links,
+    // 已选中的行对应的节点
+    chosen: Vec<usize>,
+    solution: Grid,
+    solution_cnt: u64,
+    stats: SolveStats,
+}
+
+impl DlxSolver {
+    // 重建矩阵，并覆盖题目中已给出的数所在的行；题目自相矛盾或有 1 ~ 9 以外的数时返回 false
+    fn init_search(&mut self) -> bool {
+        self.links = Links::new();
+        self.chosen.clear();
+        self.solution_cnt = 0;
+        self.stats = SolveStats::default();
+        for r in 0..9 {
+            for c in 0..9 {
+                let num = self.puzzle.0[r][c];
+                if num == 0 {
+                    continue;
+                }
+                if !(1..=9).contains(&num) {
+                    return false;
+                }
+                let columns = columns_of(r, c, num as usize);
+                if columns.iter().any(|col| self.links.is_covered(*col)) {
+                    return false;
+                }
+                for col in columns {
+                    self.links.cover(col);
+                }
+            }
+        }
+        true
+    }
+
+    fn search(&mut self, solution_cnt_needed: u64) -> bool {
+        self.stats.nodes += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.chosen.len() as u32);
+        if self.links.right[ROOT] == ROOT {
+            self.solution_cnt += 1;
+            self.solution = self.puzzle;
+            for node in &self.chosen {
+                let (r, c, num) = self.links.row[*node];
+                self.solution.0[r][c] = num;
+            }
+            return solution_cnt_needed <= self.solution_cnt;
+        }
+
+        // 选择剩余 1 最少的列
+        let mut col = self.links.right[ROOT];
+        let mut i = self.links.right[col];
+        while i != ROOT {
+            if self.links.size[i] < self.links.size[col] {
+                col = i;
+            }
+            i = self.links.right[i];
+        }
+        if self.links.size[col] == 0 {
+            return false;
+        }
+        if self.links.size[col] > 1 {
+            self.stats.guesses += 1;
+        }
+
+        self.links.cover(col);
+        let mut node = self.links.down[col];
+        while node != col {
+            self.chosen.push(node);
+            let mut j = self.links.right[node];
+            while j != node {
+                self.links.cover(self.links.column[j]);
+                j = self.links.right[j];
+            }
+
+            if self.search(solution_cnt_needed) {
+                return true;
+            }
+
+            let mut j = self.links.left[node];
+            while j != node {
+                self.links.uncover(self.links.column[j]);
+                j = self.links.left[j];
+            }
+            self.chosen.pop();
+            node = self.links.down[node];
+        }
+        self.links.uncover(col);
+
+        false
+    }
+}
+
+impl From<Grid> for DlxSolver {
+    fn from(puzzle: Grid) -> Self {
+        Self {
+            puzzle,
+            links: Links::new(),
+            chosen: Vec::with_capacity(81),
+            solution: puzzle,
+            solution_cnt: 0,
+            stats: SolveStats::default(),
+        }
+    }
+}
+
+impl Solver for DlxSolver {
+    fn any_solution(&mut self) -> Option<Grid> {
+        let start = Instant::now();
+        let found = self.init_search() && self.search(1);
+        self.stats.elapsed = start.elapsed();
+        found.then_some(self.solution)
+    }
+
+    fn count_solutions(&mut self, limit: u64) -> SolutionCount {
+        let start = Instant::now();
+        if self.init_search() {
+            self.search(limit);
+        }
+        self.stats.elapsed = start.elapsed();
+        SolutionCount::capped(self.solution_cnt, limit)
+    }
+
+    fn last_solution(&self) -> Option<Grid> {
+        (self.solution_cnt > 0).then_some(self.solution)
+    }
+
+    fn stats(&self) -> Option<&SolveStats> {
+        Some(&self.stats)
+    }
+}
@@ -1,11 +1,15 @@
-use super::{next_blank, Solver};
+use std::time::Instant;
+
+use super::{next_blank, RandomizedSolver, SolutionCount, Solutions, SolveStats, Solver};
 
 use crate::{
+    budget::{Budget, Interrupted},
+    judge::{validate, PuzzleError},
     state::{simple_state::SimpleState, Fillable, State, TrackingCandidates},
     Grid,
 };
 
-use rand::prelude::*;
+use rand::{prelude::*, rngs::StdRng};
 
 pub struct StochasticSolver<T = SimpleState>
 where
@@ -13,40 +17,80 @@ where
 {
     puzzle: Grid,
     state: T,
-    solution_cnt: u32,
+    solution: Grid,
+    solution_cnt: u64,
+    budget: Budget,
+    interrupted: Option<Interrupted>,
+    // 没有指定时使用 thread_rng，免得每次构造都要播种
+    rng: Option<StdRng>,
+    stats: SolveStats,
+    depth: u32,
 }
 
 impl<T> StochasticSolver<T>
 where
     T: State + Fillable + TrackingCandidates,
 {
+    /// Like `from`, but rejects grids which break the rules, see [`validate`].
+    pub fn new(puzzle: Grid) -> Result<Self, PuzzleError> {
+        validate(&puzzle)?;
+        Ok(Self::from(puzzle))
+    }
+
+    /// Iterate over all solutions in a deterministic order, see [`Solutions`].
+    pub fn solutions(&self) -> Solutions<T> {
+        Solutions::new(T::from(self.puzzle))
+    }
+
+    /// Return at most `n` solutions, in the same order as `solutions`.
+    pub fn solutions_up_to(&self, n: usize) -> Vec<Grid> {
+        self.solutions().take(n).collect()
+    }
+
     fn init_search(&mut self) {
         self.solution_cnt = 0;
+        self.interrupted = None;
+        self.stats = SolveStats::default();
+        self.depth = 0;
         self.state = T::from(self.puzzle);
     }
 
-    fn search(&mut self, r: usize, c: usize, solution_cnt_needed: u32) -> bool {
+    fn search(&mut self, r: usize, c: usize, solution_cnt_needed: u64) -> bool {
+        if let Err(interrupted) = self.budget.check() {
+            self.interrupted = Some(interrupted);
+            return true;
+        }
+        self.stats.nodes += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
         let coord = next_blank(r, c, &self.state);
         if coord.is_none() {
             self.solution_cnt += 1;
+            self.solution = self.state.grid();
             return true;
         }
         let (r, c) = coord.unwrap();
 
         let mut nums: Vec<i8> = (1..=9).collect();
-        nums.shuffle(&mut rand::thread_rng());
+        match &mut self.rng {
+            Some(rng) => nums.shuffle(rng),
+            None => nums.shuffle(&mut thread_rng()),
+        }
+        nums.retain(|num| self.state.is_candidate_of(r, c, *num));
+        if nums.len() > 1 {
+            self.stats.guesses += 1;
+        }
         for num in nums {
-            if self.state.is_candidate_of(r, c, num) {
-                self.state.fill_cell(r, c, num);
-
-                if self.search(r, c, solution_cnt_needed)
-                    && solution_cnt_needed <= self.solution_cnt
-                {
-                    return true;
-                }
+            self.state.fill_cell(r, c, num);
+            self.depth += 1;
 
-                self.state.unfill_cell(r, c);
+            if self.search(r, c, solution_cnt_needed)
+                && (solution_cnt_needed <= self.solution_cnt || self.interrupted.is_some())
+            {
+                return true;
             }
+
+            self.depth -= 1;
+            self.state.unfill_cell(r, c);
         }
 
         false
@@ -61,7 +105,13 @@ where
         Self {
             puzzle,
             state: T::from(puzzle),
+            solution: puzzle,
             solution_cnt: 0,
+            budget: Budget::default(),
+            interrupted: None,
+            rng: None,
+            stats: SolveStats::default(),
+            depth: 0,
         }
     }
 }
@@ -71,22 +121,60 @@ where
     T: State + Fillable + TrackingCandidates,
 {
     fn any_solution(&mut self) -> Option<Grid> {
+        let start = Instant::now();
         self.init_search();
-        if self.search(0, 0, 1) {
-            return Some(self.state.grid());
-        }
-        None
+        let found = self.search(0, 0, 1);
+        self.stats.elapsed = start.elapsed();
+        found.then(|| self.state.grid())
     }
 
-    fn solution_cnt(&mut self) -> u32 {
+    fn count_solutions(&mut self, limit: u64) -> SolutionCount {
+        let start = Instant::now();
         self.init_search();
-        self.search(0, 0, u32::MAX);
-        self.solution_cnt
+        self.search(0, 0, limit);
+        self.stats.elapsed = start.elapsed();
+        SolutionCount::capped(self.solution_cnt, limit)
     }
 
-    fn have_unique_solution(&mut self) -> bool {
-        self.init_search();
-        self.search(0, 0, 2);
-        self.solution_cnt == 1
+    fn try_any_solution(&mut self, budget: &Budget) -> Result<Option<Grid>, Interrupted> {
+        self.budget = budget.clone();
+        let solution = self.any_solution();
+        self.budget = Budget::default();
+        match self.interrupted {
+            Some(interrupted) => Err(interrupted),
+            None => Ok(solution),
+        }
+    }
+
+    fn try_count_solutions(
+        &mut self,
+        limit: u64,
+        budget: &Budget,
+    ) -> Result<SolutionCount, Interrupted> {
+        self.budget = budget.clone();
+        let solution_cnt = self.count_solutions(limit);
+        self.budget = Budget::default();
+        match self.interrupted {
+            Some(interrupted) => Err(interrupted),
+            None => Ok(solution_cnt),
+        }
+    }
+
+    fn stats(&self) -> Option<&SolveStats> {
+        Some(&self.stats)
+    }
+
+    fn last_solution(&self) -> Option<Grid> {
+        (self.solution_cnt > 0).then_some(self.solution)
+    }
+}
+
+impl<T> RandomizedSolver for StochasticSolver<T>
+where
+    T: State + Fillable + TrackingCandidates,
+{
+    fn with_rng(mut self, rng: &mut impl Rng) -> Self {
+        self.rng = Some(StdRng::seed_from_u64(rng.gen()));
+        self
     }
 }
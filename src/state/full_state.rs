@@ -9,6 +9,7 @@ use super::{
 };
 
 #[derive(Clone)]
+#[allow(clippy::type_complexity)]
 pub struct FullState {
     grid: Grid,
     candidates: [[[bool; 10]; 9]; 9],
@@ -26,6 +27,7 @@ pub struct FullState {
 }
 
 impl FullState {
+    #[allow(clippy::needless_range_loop)]
     pub fn new(grid: Grid, candidates: [[[bool; 10]; 9]; 9]) -> Self {
         let mut res = Self {
             grid,
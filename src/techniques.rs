@@ -1,17 +1,69 @@
 use std::fmt::Display;
 
-use super::state::State;
+use super::state::{
+    CandidatesSettable, Fillable, State, TrackingCandidateCountOfCell, TrackingCandidates,
+    TrackingCellCountOfCandidate,
+};
+use super::utils::{coord_2_block, overlap_region};
+use fish::{FishInfo, Jellyfish, Swordfish, XWing};
+use hidden_subsets::{
+    HiddenPair, HiddenPairBlock, HiddenPairColumn, HiddenPairInfo, HiddenPairRow,
+};
+use locked_candidates::{Claiming, ClaimingInfo, Pointing, PointingInfo};
+use naked_subsets::{
+    NakedPair, NakedPairBlock, NakedPairColumn, NakedPairInfo, NakedPairRow, NakedSubset,
+    NakedSubsetInfo,
+};
+use singles::{
+    HiddenSingle, HiddenSingleBlock, HiddenSingleColumn, HiddenSingleInfo, HiddenSingleRow,
+    NakedSingle, NakedSingleInfo,
+};
 
 pub trait Technique<T>
 where
     T: State,
 {
     fn analyze(&mut self, state: &T);
+    // 找出所有能用的步骤，每个结果只带其中一步；默认只有 analyze 找到的那一步
+    fn analyze_all(state: &T) -> Vec<Self>
+    where
+        Self: Sized + Default,
+    {
+        let mut technique = Self::default();
+        technique.analyze(state);
+        if technique.appliable() {
+            vec![technique]
+        } else {
+            vec![]
+        }
+    }
     fn appliable(&self) -> bool;
     fn score(&self) -> Option<f32>;
+    // 上一次 analyze 找到的一步
+    fn step(&self) -> Option<Step>;
+}
+
+// 只取 search 找到的第一个结果
+fn first<X>(search: impl FnOnce(&mut dyn FnMut(X) -> bool) -> bool) -> Option<X> {
+    let mut res = None;
+    search(&mut |x| {
+        res = Some(x);
+        true
+    });
+    res
+}
+
+// 取 search 找到的所有结果
+fn all<X>(search: impl FnOnce(&mut dyn FnMut(X) -> bool) -> bool) -> Vec<X> {
+    let mut res = vec![];
+    search(&mut |x| {
+        res.push(x);
+        false
+    });
+    res
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DirectOption(pub usize, pub usize, pub i8);
 
 impl Display for DirectOption {
@@ -20,14 +72,8 @@ impl Display for DirectOption {
     }
 }
 
-pub trait Direct<T>: Technique<T>
-where
-    T: State,
-{
-    fn option(&self) -> Option<DirectOption>;
-}
-
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::type_complexity)]
 pub struct ReducingCandidatesOption(pub Vec<(Vec<(usize, usize)>, Vec<i8>)>);
 
 impl Display for ReducingCandidatesOption {
@@ -56,20 +102,440 @@ impl Display for ReducingCandidatesOption {
     }
 }
 
-pub trait ReducingCandidates<T>: Technique<T>
+/// One step of a solution path: cells to fill, candidates to remove, or both at once.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    Placement(DirectOption),
+    Elimination(ReducingCandidatesOption),
+    Both(Vec<DirectOption>, ReducingCandidatesOption),
+}
+
+impl Step {
+    pub fn placements(&self) -> &[DirectOption] {
+        match self {
+            Step::Placement(option) => std::slice::from_ref(option),
+            Step::Elimination(_) => &[],
+            Step::Both(options, _) => options,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn eliminations(&self) -> &[(Vec<(usize, usize)>, Vec<i8>)] {
+        match self {
+            Step::Placement(_) => &[],
+            Step::Elimination(option) => &option.0,
+            Step::Both(_, option) => &option.0,
+        }
+    }
+
+    /// Remove the eliminated candidates from `state`, then fill the placements.
+    /// Return the candidates actually removed, which [`Step::undo`] needs.
+    pub fn apply<T>(&self, state: &mut T) -> Vec<(usize, usize, i8)>
+    where
+        T: Fillable + CandidatesSettable,
+    {
+        let mut removed = vec![];
+        for (cells, nums) in self.eliminations() {
+            for (r, c) in cells {
+                for num in nums {
+                    if state.is_candidate_of(*r, *c, *num) {
+                        state.remove_candidate_of_cell(*r, *c, *num);
+                        removed.push((*r, *c, *num));
+                    }
+                }
+            }
+        }
+        for DirectOption(r, c, num) in self.placements() {
+            state.fill_cell(*r, *c, *num);
+        }
+        removed
+    }
+
+    /// Take back an `apply` of this step to `state`, given the candidates it removed.
+    pub fn undo<T>(&self, state: &mut T, removed: Vec<(usize, usize, i8)>)
+    where
+        T: Fillable + CandidatesSettable,
+    {
+        for DirectOption(r, c, _) in self.placements().iter().rev() {
+            state.unfill_cell(*r, *c);
+        }
+        for (r, c, num) in removed {
+            state.add_candidate_of_cell(r, c, num);
+        }
+    }
+}
+
+impl From<DirectOption> for Step {
+    fn from(option: DirectOption) -> Self {
+        Step::Placement(option)
+    }
+}
+
+impl From<ReducingCandidatesOption> for Step {
+    fn from(option: ReducingCandidatesOption) -> Self {
+        Step::Elimination(option)
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Placement(option) => write!(f, "{}", option),
+            Step::Elimination(option) => write!(f, "{}", option),
+            Step::Both(options, option) => {
+                for option in options {
+                    write!(f, "{}; ", option)?;
+                }
+                write!(f, "{}", option)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TechniqueId {
+    HiddenSingle,
+    HiddenSingleRow,
+    HiddenSingleColumn,
+    HiddenSingleBlock,
+    NakedSingle,
+    Pointing,
+    Claiming,
+    NakedPair,
+    NakedPairRow,
+    NakedPairColumn,
+    NakedPairBlock,
+    HiddenPair,
+    HiddenPairRow,
+    HiddenPairColumn,
+    HiddenPairBlock,
+    NakedSubset,
+    XWing,
+    Swordfish,
+    Jellyfish,
+}
+
+/// The pattern a technique found, as reported by the technique itself.
+#[derive(Clone, Debug)]
+pub enum TechniqueInfo {
+    HiddenSingle(HiddenSingleInfo),
+    NakedSingle(NakedSingleInfo),
+    Pointing(PointingInfo),
+    Claiming(ClaimingInfo),
+    NakedPair(NakedPairInfo),
+    HiddenPair(HiddenPairInfo),
+    NakedSubset(NakedSubsetInfo),
+    Fish(FishInfo),
+}
+
+impl TechniqueInfo {
+    /// The houses in which the pattern lies.
+    pub fn houses(&self) -> Vec<House> {
+        match self {
+            TechniqueInfo::HiddenSingle(info) => vec![info.house],
+            TechniqueInfo::NakedSingle(NakedSingleInfo((r, c, _))) => vec![
+                House::Row(*r),
+                House::Column(*c),
+                House::Block(coord_2_block(*r, *c)),
+            ],
+            TechniqueInfo::Pointing(info) => vec![House::Block(info.block), info.rem_house],
+            TechniqueInfo::Claiming(info) => vec![info.house, House::Block(info.rem_block)],
+            TechniqueInfo::NakedPair(info) => vec![info.house],
+            TechniqueInfo::HiddenPair(info) => vec![info.house],
+            TechniqueInfo::NakedSubset(info) => vec![info.house],
+            TechniqueInfo::Fish(info) => info
+                .base_set
+                .iter()
+                .chain(info.cover_set.iter())
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// The cells forming the pattern, not including the cells the step changes
+    /// unless they are part of the pattern themselves.
+    pub fn cells<T>(&self, state: &T) -> Vec<(usize, usize)>
+    where
+        T: State + TrackingCandidates,
+    {
+        let holding = |cells: Vec<(usize, usize)>, num: i8| -> Vec<(usize, usize)> {
+            cells
+                .into_iter()
+                .filter(|(r, c)| state.is_cell_empty(*r, *c) && state.is_candidate_of(*r, *c, num))
+                .collect()
+        };
+        match self {
+            TechniqueInfo::HiddenSingle(info) => vec![(info.fillable.0, info.fillable.1)],
+            TechniqueInfo::NakedSingle(NakedSingleInfo((r, c, _))) => vec![(*r, *c)],
+            TechniqueInfo::Pointing(info) => holding(
+                overlap_region((2, info.block), info.rem_house.as_pair()),
+                info.rem_num,
+            ),
+            TechniqueInfo::Claiming(info) => holding(
+                overlap_region(info.house.as_pair(), (2, info.rem_block)),
+                info.rem_num,
+            ),
+            TechniqueInfo::NakedPair(info) => info.cells.to_vec(),
+            TechniqueInfo::HiddenPair(info) => vec![info.rem_cell_1, info.rem_cell_2],
+            TechniqueInfo::NakedSubset(info) => info.cells.clone(),
+            TechniqueInfo::Fish(info) => holding(info.overlap.clone(), info.candidate),
+        }
+    }
+}
+
+/// A technique applied to a state: what it found, what it does and how hard it is.
+#[derive(Clone, Debug)]
+pub struct Deduction {
+    pub technique: TechniqueId,
+    pub info: TechniqueInfo,
+    pub step: Step,
+    pub score: f32,
+}
+
+// all 为 false 时只取 analyze 找到的第一步，否则取 analyze_all 找到的所有步骤
+fn deduce<T, Tech, F>(technique: TechniqueId, state: &T, all: bool, info: F) -> Vec<Deduction>
 where
     T: State,
+    Tech: Technique<T> + Default,
+    F: Fn(&Tech) -> Option<TechniqueInfo>,
 {
-    fn option(&self) -> Option<ReducingCandidatesOption>;
+    let techs = if all {
+        Tech::analyze_all(state)
+    } else {
+        let mut tech = Tech::default();
+        tech.analyze(state);
+        vec![tech]
+    };
+    techs
+        .iter()
+        .filter_map(|tech| {
+            Some(Deduction {
+                technique,
+                info: info(tech)?,
+                step: tech.step()?,
+                score: tech.score()?,
+            })
+        })
+        .collect()
+}
+
+impl TechniqueId {
+    pub const ALL: [TechniqueId; 19] = [
+        TechniqueId::HiddenSingle,
+        TechniqueId::HiddenSingleRow,
+        TechniqueId::HiddenSingleColumn,
+        TechniqueId::HiddenSingleBlock,
+        TechniqueId::NakedSingle,
+        TechniqueId::Pointing,
+        TechniqueId::Claiming,
+        TechniqueId::NakedPair,
+        TechniqueId::NakedPairRow,
+        TechniqueId::NakedPairColumn,
+        TechniqueId::NakedPairBlock,
+        TechniqueId::HiddenPair,
+        TechniqueId::HiddenPairRow,
+        TechniqueId::HiddenPairColumn,
+        TechniqueId::HiddenPairBlock,
+        TechniqueId::NakedSubset,
+        TechniqueId::XWing,
+        TechniqueId::Swordfish,
+        TechniqueId::Jellyfish,
+    ];
+
+    /// The techniques used by default, from the easiest to the hardest.
+    pub const DEFAULT: [TechniqueId; 10] = [
+        TechniqueId::HiddenSingle,
+        TechniqueId::NakedSingle,
+        TechniqueId::Pointing,
+        TechniqueId::Claiming,
+        TechniqueId::NakedPair,
+        TechniqueId::XWing,
+        TechniqueId::HiddenPair,
+        TechniqueId::NakedSubset,
+        TechniqueId::Swordfish,
+        TechniqueId::Jellyfish,
+    ];
+
+    /// Run the technique on `state`, returning what it found.
+    pub fn analyze<T>(self, state: &T) -> Option<Deduction>
+    where
+        T: State + TrackingCandidates + TrackingCandidateCountOfCell + TrackingCellCountOfCandidate,
+    {
+        self.deduce(state, false).pop()
+    }
+
+    /// Run the technique on `state`, returning every step it can take there,
+    /// not just the first one `analyze` finds.
+    pub fn analyze_all<T>(self, state: &T) -> Vec<Deduction>
+    where
+        T: State + TrackingCandidates + TrackingCandidateCountOfCell + TrackingCellCountOfCandidate,
+    {
+        self.deduce(state, true)
+    }
+
+    fn deduce<T>(self, state: &T, all: bool) -> Vec<Deduction>
+    where
+        T: State + TrackingCandidates + TrackingCandidateCountOfCell + TrackingCellCountOfCandidate,
+    {
+        use TechniqueInfo as I;
+        match self {
+            TechniqueId::HiddenSingle => deduce(self, state, all, |t: &HiddenSingle| {
+                t.0.map(I::HiddenSingle)
+            }),
+            TechniqueId::HiddenSingleRow => deduce(self, state, all, |t: &HiddenSingleRow| {
+                t.0.map(I::HiddenSingle)
+            }),
+            TechniqueId::HiddenSingleColumn => {
+                deduce(self, state, all, |t: &HiddenSingleColumn| {
+                    t.0.map(I::HiddenSingle)
+                })
+            }
+            TechniqueId::HiddenSingleBlock => deduce(self, state, all, |t: &HiddenSingleBlock| {
+                t.0.map(I::HiddenSingle)
+            }),
+            TechniqueId::NakedSingle => {
+                deduce(self, state, all, |t: &NakedSingle| t.0.map(I::NakedSingle))
+            }
+            TechniqueId::Pointing => deduce(self, state, all, |t: &Pointing| {
+                t.0.clone().map(I::Pointing)
+            }),
+            TechniqueId::Claiming => deduce(self, state, all, |t: &Claiming| {
+                t.0.clone().map(I::Claiming)
+            }),
+            TechniqueId::NakedPair => deduce(self, state, all, |t: &NakedPair| {
+                t.0.clone().map(I::NakedPair)
+            }),
+            TechniqueId::NakedPairRow => deduce(self, state, all, |t: &NakedPairRow| {
+                t.0.clone().map(I::NakedPair)
+            }),
+            TechniqueId::NakedPairColumn => deduce(self, state, all, |t: &NakedPairColumn| {
+                t.0.clone().map(I::NakedPair)
+            }),
+            TechniqueId::NakedPairBlock => deduce(self, state, all, |t: &NakedPairBlock| {
+                t.0.clone().map(I::NakedPair)
+            }),
+            TechniqueId::HiddenPair => deduce(self, state, all, |t: &HiddenPair| {
+                t.0.clone().map(I::HiddenPair)
+            }),
+            TechniqueId::HiddenPairRow => deduce(self, state, all, |t: &HiddenPairRow| {
+                t.0.clone().map(I::HiddenPair)
+            }),
+            TechniqueId::HiddenPairColumn => deduce(self, state, all, |t: &HiddenPairColumn| {
+                t.0.clone().map(I::HiddenPair)
+            }),
+            TechniqueId::HiddenPairBlock => deduce(self, state, all, |t: &HiddenPairBlock| {
+                t.0.clone().map(I::HiddenPair)
+            }),
+            TechniqueId::NakedSubset => deduce(self, state, all, |t: &NakedSubset| {
+                t.0.clone().map(I::NakedSubset)
+            }),
+            TechniqueId::XWing => deduce(self, state, all, |t: &XWing| t.0.clone().map(I::Fish)),
+            TechniqueId::Swordfish => {
+                deduce(self, state, all, |t: &Swordfish| t.0.clone().map(I::Fish))
+            }
+            TechniqueId::Jellyfish => {
+                deduce(self, state, all, |t: &Jellyfish| t.0.clone().map(I::Fish))
+            }
+        }
+    }
+}
+
+impl Display for TechniqueId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TechniqueId::HiddenSingle => "Hidden Single",
+            TechniqueId::HiddenSingleRow => "Hidden Single in Row",
+            TechniqueId::HiddenSingleColumn => "Hidden Single in Column",
+            TechniqueId::HiddenSingleBlock => "Hidden Single in Block",
+            TechniqueId::NakedSingle => "Naked Single",
+            TechniqueId::Pointing => "Pointing",
+            TechniqueId::Claiming => "Claiming",
+            TechniqueId::NakedPair => "Naked Pair",
+            TechniqueId::NakedPairRow => "Naked Pair in Row",
+            TechniqueId::NakedPairColumn => "Naked Pair in Column",
+            TechniqueId::NakedPairBlock => "Naked Pair in Block",
+            TechniqueId::HiddenPair => "Hidden Pair",
+            TechniqueId::HiddenPairRow => "Hidden Pair in Row",
+            TechniqueId::HiddenPairColumn => "Hidden Pair in Column",
+            TechniqueId::HiddenPairBlock => "Hidden Pair in Block",
+            TechniqueId::NakedSubset => "Naked Subset",
+            TechniqueId::XWing => "X-Wing",
+            TechniqueId::Swordfish => "Swordfish",
+            TechniqueId::Jellyfish => "Jellyfish",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A coarse grouping of techniques by the kind of pattern they look for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TechniqueCategory {
+    Single,
+    Intersection,
+    Subset,
+    Fish,
+}
+
+impl TechniqueId {
+    /// The technique of [`TechniqueId::DEFAULT`] this one is a variant of,
+    /// e.g. `HiddenSingle` for `HiddenSingleRow`. A default technique is its own family.
+    pub fn family(self) -> TechniqueId {
+        match self {
+            TechniqueId::HiddenSingleRow
+            | TechniqueId::HiddenSingleColumn
+            | TechniqueId::HiddenSingleBlock => TechniqueId::HiddenSingle,
+            TechniqueId::NakedPairRow
+            | TechniqueId::NakedPairColumn
+            | TechniqueId::NakedPairBlock => TechniqueId::NakedPair,
+            TechniqueId::HiddenPairRow
+            | TechniqueId::HiddenPairColumn
+            | TechniqueId::HiddenPairBlock => TechniqueId::HiddenPair,
+            _ => self,
+        }
+    }
+
+    pub fn category(self) -> TechniqueCategory {
+        match self {
+            TechniqueId::HiddenSingle
+            | TechniqueId::HiddenSingleRow
+            | TechniqueId::HiddenSingleColumn
+            | TechniqueId::HiddenSingleBlock
+            | TechniqueId::NakedSingle => TechniqueCategory::Single,
+            TechniqueId::Pointing | TechniqueId::Claiming => TechniqueCategory::Intersection,
+            TechniqueId::NakedPair
+            | TechniqueId::NakedPairRow
+            | TechniqueId::NakedPairColumn
+            | TechniqueId::NakedPairBlock
+            | TechniqueId::HiddenPair
+            | TechniqueId::HiddenPairRow
+            | TechniqueId::HiddenPairColumn
+            | TechniqueId::HiddenPairBlock
+            | TechniqueId::NakedSubset => TechniqueCategory::Subset,
+            TechniqueId::XWing | TechniqueId::Swordfish | TechniqueId::Jellyfish => {
+                TechniqueCategory::Fish
+            }
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum House {
     Row(usize),
     Column(usize),
     Block(usize),
 }
 
+impl House {
+    // 转换为 overlap_region 使用的 (单元类型, 单元序号) 形式
+    pub fn as_pair(&self) -> (usize, usize) {
+        match self {
+            House::Row(r) => (0, *r),
+            House::Column(c) => (1, *c),
+            House::Block(b) => (2, *b),
+        }
+    }
+}
+
 pub mod fish;
 pub mod hidden_subsets;
 pub mod locked_candidates;
@@ -2,9 +2,17 @@ use itertools::Itertools;
 
 use crate::state::{State, TrackingCandidates, TrackingCellCountOfCandidate};
 
-use super::{House, ReducingCandidates, ReducingCandidatesOption, Technique};
+use super::{all, first, House, ReducingCandidatesOption, Step, Technique};
 
 fn basic_fish_row_base<T>(state: &T, size: usize) -> Option<FishInfo>
+where
+    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
+{
+    first(|found| basic_fishes_row_base(state, size, found))
+}
+
+// 依次把找到的每条以行为 base set 的鱼交给 found，found 返回 true 时停止并返回 true
+fn basic_fishes_row_base<T>(state: &T, size: usize, found: &mut dyn FnMut(FishInfo) -> bool) -> bool
 where
     T: State + TrackingCandidates + TrackingCellCountOfCandidate,
 {
@@ -71,20 +79,30 @@ where
                 continue 'outer;
             }
 
-            return Some(FishInfo {
+            if found(FishInfo {
                 size,
                 base_set: base_rows.iter().map(|r| House::Row(*r)).collect(),
                 cover_set: cover_cols.iter().map(|c| House::Column(*c)).collect(),
                 overlap: overlap.collect_vec(),
                 candidate: num,
                 rem_cells: remove,
-            });
+            }) {
+                return true;
+            }
         }
     }
-    None
+    false
 }
 
 fn basic_fish_col_base<T>(state: &T, size: usize) -> Option<FishInfo>
+where
+    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
+{
+    first(|found| basic_fishes_col_base(state, size, found))
+}
+
+// 依次把找到的每条以列为 base set 的鱼交给 found，found 返回 true 时停止并返回 true
+fn basic_fishes_col_base<T>(state: &T, size: usize, found: &mut dyn FnMut(FishInfo) -> bool) -> bool
 where
     T: State + TrackingCandidates + TrackingCellCountOfCandidate,
 {
@@ -151,17 +169,29 @@ where
                 continue 'outer;
             }
 
-            return Some(FishInfo {
+            if found(FishInfo {
                 size,
                 base_set: base_cols.iter().map(|c| House::Column(*c)).collect(),
                 cover_set: cover_rows.iter().map(|r| House::Row(*r)).collect(),
                 overlap: overlap.collect_vec(),
                 candidate: num,
                 rem_cells: remove,
-            });
+            }) {
+                return true;
+            }
         }
     }
-    None
+    false
+}
+
+// 所有 size 条 base set 的鱼，先行后列
+fn basic_fish_infos<T>(state: &T, size: usize) -> Vec<FishInfo>
+where
+    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
+{
+    let mut infos = all(|found| basic_fishes_row_base(state, size, found));
+    infos.extend(all(|found| basic_fishes_col_base(state, size, found)));
+    infos
 }
 
 #[derive(Clone, Debug)]
@@ -187,6 +217,12 @@ where
         }
         self.0 = basic_fish_col_base(state, 2);
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        basic_fish_infos(state, 2)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -196,15 +232,13 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for XWing
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
-        self.0
-            .clone()
-            .map(|info| ReducingCandidatesOption(vec![(info.rem_cells, vec![info.candidate])]))
+    fn step(&self) -> Option<Step> {
+        self.0.clone().map(|info| {
+            Step::Elimination(ReducingCandidatesOption(vec![(
+                info.rem_cells,
+                vec![info.candidate],
+            )]))
+        })
     }
 }
 
@@ -221,6 +255,12 @@ where
         }
         self.0 = basic_fish_col_base(state, 3);
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        basic_fish_infos(state, 3)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -230,15 +270,13 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for Swordfish
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
-        self.0
-            .clone()
-            .map(|info| ReducingCandidatesOption(vec![(info.rem_cells, vec![info.candidate])]))
+    fn step(&self) -> Option<Step> {
+        self.0.clone().map(|info| {
+            Step::Elimination(ReducingCandidatesOption(vec![(
+                info.rem_cells,
+                vec![info.candidate],
+            )]))
+        })
     }
 }
 
@@ -255,6 +293,12 @@ where
         }
         self.0 = basic_fish_col_base(state, 4);
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        basic_fish_infos(state, 4)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -264,14 +308,12 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for Jellyfish
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
-        self.0
-            .clone()
-            .map(|info| ReducingCandidatesOption(vec![(info.rem_cells, vec![info.candidate])]))
+    fn step(&self) -> Option<Step> {
+        self.0.clone().map(|info| {
+            Step::Elimination(ReducingCandidatesOption(vec![(
+                info.rem_cells,
+                vec![info.candidate],
+            )]))
+        })
     }
 }
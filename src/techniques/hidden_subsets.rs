@@ -3,13 +3,22 @@ use crate::{
     utils::block_idx_2_coord,
 };
 
-use super::{House, ReducingCandidates, ReducingCandidatesOption, Technique};
+use super::{first, House, ReducingCandidatesOption, Step, Technique};
 
 fn hidden_pair<T, F1, F2>(
     state: &T,
     cell_cnt_of_candidate: F1,
     coord_transform: F2,
-) -> Option<(
+) -> Option<HiddenPairFound>
+where
+    T: State + TrackingCandidates,
+    F1: Fn(&T, usize, i8) -> i8,
+    F2: Fn(usize, usize) -> (usize, usize),
+{
+    first(|found| hidden_pairs(state, &cell_cnt_of_candidate, &coord_transform, found))
+}
+
+type HiddenPairFound = (
     (usize, usize),
     Vec<i8>,
     (usize, usize),
@@ -17,7 +26,15 @@ fn hidden_pair<T, F1, F2>(
     i8,
     i8,
     usize,
-)>
+);
+
+// 依次把找到的每个 hidden pair 交给 found，found 返回 true 时停止并返回 true
+fn hidden_pairs<T, F1, F2>(
+    state: &T,
+    cell_cnt_of_candidate: &F1,
+    coord_transform: &F2,
+    found: &mut dyn FnMut(HiddenPairFound) -> bool,
+) -> bool
 where
     T: State + TrackingCandidates,
     F1: Fn(&T, usize, i8) -> i8,
@@ -50,8 +67,8 @@ where
                     let removes_2: Vec<i8> = (1..=9)
                         .filter(|n| *n != num1 && *n != num2 && state.is_candidate_of(r2, c2, *n))
                         .collect();
-                    if !removes_1.is_empty() || !removes_2.is_empty() {
-                        return Some((
+                    if (!removes_1.is_empty() || !removes_2.is_empty())
+                        && found((
                             (r1, c1),
                             removes_1,
                             (r2, c2),
@@ -59,13 +76,55 @@ where
                             nums[i1],
                             nums[i2],
                             i,
-                        ));
+                        ))
+                    {
+                        return true;
                     }
                 }
             }
         }
     }
-    None
+    false
+}
+
+// 某一类单元中所有的 hidden pair
+fn hidden_pair_infos<T>(state: &T, house: fn(usize) -> House) -> Vec<HiddenPairInfo>
+where
+    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
+{
+    let mut infos = vec![];
+    let mut found = |res: HiddenPairFound| {
+        infos.push(HiddenPairInfo {
+            house: house(res.6),
+            nums: [res.4, res.5],
+            rem_cell_1: res.0,
+            rem_nums_1: res.1,
+            rem_cell_2: res.2,
+            rem_nums_2: res.3,
+        });
+        false
+    };
+    match house(0) {
+        House::Row(_) => hidden_pairs(
+            state,
+            &|p: &T, r, num| p.cell_cnt_of_candidate_in_row(r, num),
+            &|r, c| (r, c),
+            &mut found,
+        ),
+        House::Column(_) => hidden_pairs(
+            state,
+            &|p: &T, c, num| p.cell_cnt_of_candidate_in_col(c, num),
+            &|c, r| (r, c),
+            &mut found,
+        ),
+        House::Block(_) => hidden_pairs(
+            state,
+            &|p: &T, b, num| p.cell_cnt_of_candidate_in_blk(b, num),
+            &block_idx_2_coord,
+            &mut found,
+        ),
+    };
+    infos
 }
 
 #[derive(Clone, Debug)]
@@ -129,6 +188,13 @@ where
             }
         }
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        [House::Block, House::Row, House::Column]
+            .into_iter()
+            .flat_map(|house| hidden_pair_infos(state, house))
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -138,17 +204,12 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for HiddenPair
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(vec![
+            Step::Elimination(ReducingCandidatesOption(vec![
                 (vec![info.rem_cell_1], info.rem_nums_1),
                 (vec![info.rem_cell_2], info.rem_nums_2),
-            ])
+            ]))
         })
     }
 }
@@ -174,6 +235,12 @@ where
             rem_nums_2: res.3,
         });
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        hidden_pair_infos(state, House::Row)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -183,17 +250,12 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for HiddenPairRow
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(vec![
+            Step::Elimination(ReducingCandidatesOption(vec![
                 (vec![info.rem_cell_1], info.rem_nums_1),
                 (vec![info.rem_cell_2], info.rem_nums_2),
-            ])
+            ]))
         })
     }
 }
@@ -219,6 +281,12 @@ where
             rem_nums_2: res.3,
         })
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        hidden_pair_infos(state, House::Column)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -228,17 +296,12 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for HiddenPairColumn
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(vec![
+            Step::Elimination(ReducingCandidatesOption(vec![
                 (vec![info.rem_cell_1], info.rem_nums_1),
                 (vec![info.rem_cell_2], info.rem_nums_2),
-            ])
+            ]))
         })
     }
 }
@@ -264,6 +327,12 @@ where
             rem_nums_2: res.3,
         });
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        hidden_pair_infos(state, House::Block)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -273,17 +342,12 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for HiddenPairBlock
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(vec![
+            Step::Elimination(ReducingCandidatesOption(vec![
                 (vec![info.rem_cell_1], info.rem_nums_1),
                 (vec![info.rem_cell_2], info.rem_nums_2),
-            ])
+            ]))
         })
     }
 }
@@ -3,12 +3,17 @@ use crate::{
     utils::{block_idx_2_coord, count_one},
 };
 
-use super::{House, ReducingCandidates, ReducingCandidatesOption, Technique};
+use super::{first, House, ReducingCandidatesOption, Step, Technique};
 
-fn naked_pair<T, F>(
-    state: &T,
-    coord_transform: F,
-) -> Option<(
+fn naked_pair<T, F>(state: &T, coord_transform: F) -> Option<NakedPairFound>
+where
+    T: State + TrackingCandidates + TrackingCandidateCountOfCell,
+    F: Fn(usize, usize) -> (usize, usize),
+{
+    first(|found| naked_pairs(state, &coord_transform, found))
+}
+
+type NakedPairFound = (
     (usize, usize),
     (usize, usize),
     i8,
@@ -16,7 +21,14 @@ fn naked_pair<T, F>(
     i8,
     Vec<(usize, usize)>,
     usize,
-)>
+);
+
+// 依次把找到的每个 naked pair 交给 found，found 返回 true 时停止并返回 true
+fn naked_pairs<T, F>(
+    state: &T,
+    coord_transform: &F,
+    found: &mut dyn FnMut(NakedPairFound) -> bool,
+) -> bool
 where
     T: State + TrackingCandidates + TrackingCandidateCountOfCell,
     F: Fn(usize, usize) -> (usize, usize),
@@ -61,14 +73,41 @@ where
                         })
                         .map(|j| coord_transform(i, j))
                         .collect();
-                    if !removes_1.is_empty() || !removes_2.is_empty() {
-                        return Some(((r1, c1), (r2, c2), num1, removes_1, num2, removes_2, i));
+                    if (!removes_1.is_empty() || !removes_2.is_empty())
+                        && found(((r1, c1), (r2, c2), num1, removes_1, num2, removes_2, i))
+                    {
+                        return true;
                     }
                 }
             }
         }
     }
-    None
+    false
+}
+
+// 某一类单元中所有的 naked pair
+fn naked_pair_infos<T>(state: &T, house: fn(usize) -> House) -> Vec<NakedPairInfo>
+where
+    T: State + TrackingCandidates + TrackingCandidateCountOfCell,
+{
+    let mut infos = vec![];
+    let mut found = |res: NakedPairFound| {
+        infos.push(NakedPairInfo {
+            house: house(res.6),
+            cells: [res.0, res.1],
+            rem_cells_1: res.3,
+            rem_num_1: res.2,
+            rem_cells_2: res.5,
+            rem_num_2: res.4,
+        });
+        false
+    };
+    match house(0) {
+        House::Row(_) => naked_pairs(state, &|r, c| (r, c), &mut found),
+        House::Column(_) => naked_pairs(state, &|c, r| (r, c), &mut found),
+        House::Block(_) => naked_pairs(state, &block_idx_2_coord, &mut found),
+    };
+    infos
 }
 
 #[derive(Clone, Debug)]
@@ -117,6 +156,13 @@ where
             }
         }
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        [House::Block, House::Row, House::Column]
+            .into_iter()
+            .flat_map(|house| naked_pair_infos(state, house))
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -126,17 +172,12 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for NakedPair
-where
-    T: State + TrackingCandidates + TrackingCandidateCountOfCell,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(vec![
+            Step::Elimination(ReducingCandidatesOption(vec![
                 (info.rem_cells_1, vec![info.rem_num_1]),
                 (info.rem_cells_2, vec![info.rem_num_2]),
-            ])
+            ]))
         })
     }
 }
@@ -157,6 +198,12 @@ where
             rem_num_2: res.4,
         })
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        naked_pair_infos(state, House::Row)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -166,17 +213,12 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for NakedPairRow
-where
-    T: State + TrackingCandidates + TrackingCandidateCountOfCell,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(vec![
+            Step::Elimination(ReducingCandidatesOption(vec![
                 (info.rem_cells_1, vec![info.rem_num_1]),
                 (info.rem_cells_2, vec![info.rem_num_2]),
-            ])
+            ]))
         })
     }
 }
@@ -197,6 +239,12 @@ where
             rem_num_2: res.4,
         })
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        naked_pair_infos(state, House::Column)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -206,17 +254,12 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for NakedPairColumn
-where
-    T: State + TrackingCandidates + TrackingCandidateCountOfCell,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(vec![
+            Step::Elimination(ReducingCandidatesOption(vec![
                 (info.rem_cells_1, vec![info.rem_num_1]),
                 (info.rem_cells_2, vec![info.rem_num_2]),
-            ])
+            ]))
         })
     }
 }
@@ -237,6 +280,12 @@ where
             rem_num_2: res.4,
         })
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        naked_pair_infos(state, House::Block)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -246,31 +295,38 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for NakedPairBlock
-where
-    T: State + TrackingCandidates + TrackingCandidateCountOfCell,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(vec![
+            Step::Elimination(ReducingCandidatesOption(vec![
                 (info.rem_cells_1, vec![info.rem_num_1]),
                 (info.rem_cells_2, vec![info.rem_num_2]),
-            ])
+            ]))
         })
     }
 }
 
-fn naked_subset<T, F>(
-    k: usize,
-    state: &T,
-    coord_transform: F,
-) -> Option<(
+fn naked_subset<T, F>(k: usize, state: &T, coord_transform: F) -> Option<NakedSubsetFound>
+where
+    T: State + TrackingCandidates,
+    F: Fn(usize, usize) -> (usize, usize),
+{
+    first(|found| naked_subsets(k, state, &coord_transform, found))
+}
+
+type NakedSubsetFound = (
     usize,                          // house
     Vec<(usize, usize)>,            // cells
     Vec<i8>,                        // nums
     Vec<(Vec<(usize, usize)>, i8)>, // remove
-)>
+);
+
+// 依次把找到的每个 k 格的 naked subset 交给 found，found 返回 true 时停止并返回 true
+fn naked_subsets<T, F>(
+    k: usize,
+    state: &T,
+    coord_transform: &F,
+    found: &mut dyn FnMut(NakedSubsetFound) -> bool,
+) -> bool
 where
     T: State + TrackingCandidates,
     F: Fn(usize, usize) -> (usize, usize),
@@ -334,19 +390,21 @@ where
                             }
                         })
                         .collect();
-                    if !rems.is_empty() {
-                        return Some((
+                    if !rems.is_empty()
+                        && found((
                             i,
                             cells_j.clone().map(|j| coord_transform(i, j)).collect(),
                             nums,
                             rems,
-                        ));
+                        ))
+                    {
+                        return true;
                     }
                 }
             }
         }
     }
-    None
+    false
 }
 
 #[derive(Clone, Debug)]
@@ -358,6 +416,30 @@ pub struct NakedSubsetInfo {
     pub removes: Vec<(Vec<(usize, usize)>, i8)>,
 }
 
+// 某一类单元中所有 k 格的 naked subset
+fn naked_subset_infos<T>(k: usize, state: &T, house: fn(usize) -> House) -> Vec<NakedSubsetInfo>
+where
+    T: State + TrackingCandidates,
+{
+    let mut infos = vec![];
+    let mut found = |(i, cells, nums, removes): NakedSubsetFound| {
+        infos.push(NakedSubsetInfo {
+            k,
+            house: house(i),
+            cells,
+            nums,
+            removes,
+        });
+        false
+    };
+    match house(0) {
+        House::Row(_) => naked_subsets(k, state, &|r, c| (r, c), &mut found),
+        House::Column(_) => naked_subsets(k, state, &|r, c| (c, r), &mut found),
+        House::Block(_) => naked_subsets(k, state, &block_idx_2_coord, &mut found),
+    };
+    infos
+}
+
 #[derive(Default)]
 pub struct NakedSubset(pub Option<NakedSubsetInfo>);
 impl<T> Technique<T> for NakedSubset
@@ -407,6 +489,16 @@ where
             }
         }
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        (3..=4)
+            .flat_map(|k| {
+                [House::Block, House::Row, House::Column]
+                    .into_iter()
+                    .flat_map(move |house| naked_subset_infos(k, state, house))
+            })
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -420,19 +512,14 @@ where
         }
         None
     }
-}
-impl<T> ReducingCandidates<T> for NakedSubset
-where
-    T: State + TrackingCandidates,
-{
-    fn option(&self) -> Option<ReducingCandidatesOption> {
+    fn step(&self) -> Option<Step> {
         self.0.clone().map(|info| {
-            ReducingCandidatesOption(
+            Step::Elimination(ReducingCandidatesOption(
                 info.removes
                     .into_iter()
                     .map(|(rem_cells, rem_num)| (rem_cells, vec![rem_num]))
                     .collect(),
-            )
+            ))
         })
     }
 }
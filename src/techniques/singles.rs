@@ -5,13 +5,28 @@ use crate::{
     utils::block_idx_2_coord,
 };
 
-use super::{Direct, DirectOption, House, Technique};
+use super::{all, first, DirectOption, House, Step, Technique};
 
 fn hidden_single<T, F1, F2>(
     state: &T,
     cell_cnt_of_candidate: F1,
     coord_transform: F2,
 ) -> Option<(usize, usize, i8, usize)>
+where
+    T: State + TrackingCandidates,
+    F1: Fn(&T, usize, i8) -> i8,
+    F2: Fn(usize, usize) -> (usize, usize),
+{
+    first(|found| hidden_singles(state, &cell_cnt_of_candidate, &coord_transform, found))
+}
+
+// 依次把找到的每个 hidden single 交给 found，found 返回 true 时停止并返回 true
+fn hidden_singles<T, F1, F2>(
+    state: &T,
+    cell_cnt_of_candidate: &F1,
+    coord_transform: &F2,
+    found: &mut dyn FnMut((usize, usize, i8, usize)) -> bool,
+) -> bool
 where
     T: State + TrackingCandidates,
     F1: Fn(&T, usize, i8) -> i8,
@@ -21,18 +36,55 @@ where
         for num in 1..=9 {
             if cell_cnt_of_candidate(state, i, num) == 1 {
                 let j = (0..9)
-                    .filter(|j: &usize| {
+                    .find(|j: &usize| {
                         let (r, c) = coord_transform(i, *j);
                         state.is_cell_empty(r, c) && state.is_candidate_of(r, c, num)
                     })
-                    .next()
                     .unwrap();
                 let (r, c) = coord_transform(i, j);
-                return Some((r, c, num, i));
+                if found((r, c, num, i)) {
+                    return true;
+                }
             }
         }
     }
-    None
+    false
+}
+
+// 某一类单元中所有的 hidden single
+fn hidden_single_infos<T>(state: &T, house: fn(usize) -> House) -> Vec<HiddenSingleInfo>
+where
+    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
+{
+    let mut infos = vec![];
+    let mut found = |res: (usize, usize, i8, usize)| {
+        infos.push(HiddenSingleInfo {
+            house: house(res.3),
+            fillable: (res.0, res.1, res.2),
+        });
+        false
+    };
+    match house(0) {
+        House::Row(_) => hidden_singles(
+            state,
+            &|p: &T, r, num| p.cell_cnt_of_candidate_in_row(r, num),
+            &|r, c| (r, c),
+            &mut found,
+        ),
+        House::Column(_) => hidden_singles(
+            state,
+            &|p: &T, c, num| p.cell_cnt_of_candidate_in_col(c, num),
+            &|c, r| (r, c),
+            &mut found,
+        ),
+        House::Block(_) => hidden_singles(
+            state,
+            &|p: &T, b, num| p.cell_cnt_of_candidate_in_blk(b, num),
+            &block_idx_2_coord,
+            &mut found,
+        ),
+    };
+    infos
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -80,6 +132,13 @@ where
             }
         }
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        [House::Block, House::Row, House::Column]
+            .into_iter()
+            .flat_map(|house| hidden_single_infos(state, house))
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
@@ -90,14 +149,14 @@ where
             House::Block(_) => 1.2,
         })
     }
-}
-impl<T> Direct<T> for HiddenSingle
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<DirectOption> {
-        self.0
-            .map(|info| DirectOption(info.fillable.0, info.fillable.1, info.fillable.2))
+    fn step(&self) -> Option<Step> {
+        self.0.map(|info| {
+            Step::Placement(DirectOption(
+                info.fillable.0,
+                info.fillable.1,
+                info.fillable.2,
+            ))
+        })
     }
 }
 
@@ -118,20 +177,26 @@ where
             fillable: (res.0, res.1, res.2),
         });
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        hidden_single_infos(state, House::Row)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
     fn score(&self) -> Option<f32> {
         self.0.map(|_| 1.5)
     }
-}
-impl<T> Direct<T> for HiddenSingleRow
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<DirectOption> {
-        self.0
-            .map(|info| DirectOption(info.fillable.0, info.fillable.1, info.fillable.2))
+    fn step(&self) -> Option<Step> {
+        self.0.map(|info| {
+            Step::Placement(DirectOption(
+                info.fillable.0,
+                info.fillable.1,
+                info.fillable.2,
+            ))
+        })
     }
 }
 
@@ -152,20 +217,26 @@ where
             fillable: (res.0, res.1, res.2),
         });
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        hidden_single_infos(state, House::Column)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
     fn score(&self) -> Option<f32> {
         self.0.map(|_| 1.5)
     }
-}
-impl<T> Direct<T> for HiddenSingleColumn
-where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
-{
-    fn option(&self) -> Option<DirectOption> {
-        self.0
-            .map(|info| DirectOption(info.fillable.0, info.fillable.1, info.fillable.2))
+    fn step(&self) -> Option<Step> {
+        self.0.map(|info| {
+            Step::Placement(DirectOption(
+                info.fillable.0,
+                info.fillable.1,
+                info.fillable.2,
+            ))
+        })
     }
 }
 
@@ -186,21 +257,46 @@ where
             fillable: (res.0, res.1, res.2),
         });
     }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        hidden_single_infos(state, House::Block)
+            .into_iter()
+            .map(|info| Self(Some(info)))
+            .collect()
+    }
     fn appliable(&self) -> bool {
         self.0.is_some()
     }
     fn score(&self) -> Option<f32> {
         self.0.map(|_| 1.2)
     }
+    fn step(&self) -> Option<Step> {
+        self.0.map(|info| {
+            Step::Placement(DirectOption(
+                info.fillable.0,
+                info.fillable.1,
+                info.fillable.2,
+            ))
+        })
+    }
 }
-impl<T> Direct<T> for HiddenSingleBlock
+
+fn naked_singles<T>(state: &T, found: &mut dyn FnMut((usize, usize, i8)) -> bool) -> bool
 where
-    T: State + TrackingCandidates + TrackingCellCountOfCandidate,
+    T: State + TrackingCandidates + TrackingCandidateCountOfCell,
 {
-    fn option(&self) -> Option<DirectOption> {
-        self.0
-            .map(|info| DirectOption(info.fillable.0, info.fillable.1, info.fillable.2))
+    for r in 0..9 {
+        for c in 0..9 {
+            if state.is_cell_empty(r, c) && state.candidate_cnt_of_cell(r, c) == 1 {
+                let num = (1..=9)
+                    .find(|num| state.is_candidate_of(r, c, *num))
+                    .unwrap();
+                if found((r, c, num)) {
+                    return true;
+                }
+            }
+        }
     }
+    false
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -213,20 +309,13 @@ where
     T: State + TrackingCandidates + TrackingCandidateCountOfCell,
 {
     fn analyze(&mut self, state: &T) {
-        self.0 = (|| {
-            for r in 0..9 {
-                for c in 0..9 {
-                    if state.is_cell_empty(r, c) && state.candidate_cnt_of_cell(r, c) == 1 {
-                        let num = (1..=9)
-                            .filter(|num| state.is_candidate_of(r, c, *num))
-                            .next()
-                            .unwrap();
-                        return Some(NakedSingleInfo((r, c, num)));
-                    }
-                }
-            }
-            None
-        })()
+        self.0 = first(|found| naked_singles(state, found)).map(NakedSingleInfo);
+    }
+    fn analyze_all(state: &T) -> Vec<Self> {
+        all(|found| naked_singles(state, found))
+            .into_iter()
+            .map(|res| Self(Some(NakedSingleInfo(res))))
+            .collect()
     }
     fn appliable(&self) -> bool {
         self.0.is_some()
@@ -234,13 +323,8 @@ where
     fn score(&self) -> Option<f32> {
         self.0.map(|_| 2.3)
     }
-}
-impl<T> Direct<T> for NakedSingle
-where
-    T: State + TrackingCandidates + TrackingCandidateCountOfCell,
-{
-    fn option(&self) -> Option<DirectOption> {
+    fn step(&self) -> Option<Step> {
         self.0
-            .map(|info| DirectOption(info.0 .0, info.0 .1, info.0 .2))
+            .map(|info| Step::Placement(DirectOption(info.0 .0, info.0 .1, info.0 .2)))
     }
 }
@@ -1,19 +1,57 @@
-use rand::random;
+use std::{collections::BTreeMap, num::NonZeroUsize, time::Duration};
+
+use rand::{random, rngs::StdRng, SeedableRng};
 
 use crate::{
+    analysis::{
+        backdoor_size, feature_names, features, is_minimal, make_minimal, predict_solve_time,
+        redundant_clues, solves_with, SolveTimeModel,
+    },
+    batch::{rate, rate_batch, BatchConfig, BatchError},
+    budget::{Budget, CancellationToken, Interrupted},
+    explain::{explain, Chinese, English},
     generator::{
-        random_sudoku_puzzle_easy, random_sudoku_puzzle_extraeasy, random_sudoku_puzzle_extrahard,
-        random_sudoku_puzzle_hard, random_sudoku_puzzle_normal, random_sudoku_puzzle_ultimate,
+        classify, generate, generate_requiring, generate_with_mask, random_sudoku_puzzle,
+        random_sudoku_puzzle_easy, random_sudoku_puzzle_easy_with_rng,
+        random_sudoku_puzzle_extraeasy, random_sudoku_puzzle_extrahard, random_sudoku_puzzle_hard,
+        random_sudoku_puzzle_normal, random_sudoku_puzzle_normal_with_rng,
+        random_sudoku_puzzle_ultimate, random_sudoku_puzzle_ultimate_with_rng,
+        random_sudoku_puzzle_with_rng, try_random_sudoku_puzzle, Difficulty, GenConfig, MaskError,
+        Symmetry,
+    },
+    hint::{hint, HintLevel},
+    judge::{judge_sudoku, validate, PuzzleError},
+    sat::{Cnf, SatSolver},
+    se::{SERater, SETechnique, SE_TECHNIQUES},
+    solver::{
+        advanced::{AdvancedSolver, SolverConfig, StepPolicy},
+        bitboard::BitboardSolver,
+        dlx::DlxSolver,
+        stochastic::StochasticSolver,
+        Grader, RandomizedSolver, SolutionCount, SolveStats, Solver,
     },
-    judge::judge_sudoku,
-    solver::{advanced::AdvancedSolver, stochastic::StochasticSolver, Solver},
     state::{
         full_state::FullState, simple_state::SimpleState, CandidatesSettable, Fillable, State,
         TrackingCandidateCountOfCell, TrackingCandidates, TrackingCellCountOfCandidate,
     },
+    techniques::{
+        locked_candidates::PointingInfo, DirectOption, House, TechniqueCategory, TechniqueId,
+        TechniqueInfo,
+    },
     utils::{block_idx_2_coord, coord_2_block_idx, overlap_region},
+    verify::verify_solving_path,
+    Grid,
 };
 
+// 挖空 puzzle 的解的对角线：每个空格都是所在行、列、宫唯一的空格
+fn diagonal_blanks(puzzle: Grid) -> Grid {
+    let mut easy = DlxSolver::from(puzzle).any_solution().unwrap();
+    for i in 0..9 {
+        easy.0[i][i] = 0;
+    }
+    easy
+}
+
 #[test]
 fn sudoku_state() {
     for _ in 0..100 {
@@ -30,8 +68,7 @@ fn sudoku_state() {
             moves.push((r, c));
             puzzle.fill_cell(r, c, num);
         }
-        while !moves.is_empty() {
-            let (r, c) = moves.pop().unwrap();
+        while let Some((r, c)) = moves.pop() {
             puzzle.unfill_cell(r, c);
         }
 
@@ -157,6 +194,1034 @@ fn sudoku_solver() {
     }
 }
 
+#[test]
+fn dlx_solver() {
+    for i in 0..50 {
+        let puzzle = random_sudoku_puzzle_ultimate();
+        let mut solver = DlxSolver::from(puzzle);
+        assert!(solver.have_unique_solution());
+        let solution = solver.any_solution().unwrap();
+        assert!(judge_sudoku(&solution).1);
+        assert_eq!(
+            solution,
+            AdvancedSolver::<FullState>::from(puzzle)
+                .any_solution()
+                .unwrap()
+        );
+
+        if i >= 3 {
+            continue;
+        }
+        // 挖去一个数后解的个数应与 AdvancedSolver 一致
+        let mut sparse = puzzle;
+        for cell in sparse.0.iter_mut().flatten().filter(|v| **v > 0).take(1) {
+            *cell = 0;
+        }
+        assert_eq!(
+            DlxSolver::from(sparse).solution_cnt(),
+            AdvancedSolver::<FullState>::from(sparse).solution_cnt()
+        );
+    }
+
+    let mut invalid = Grid([[0; 9]; 9]);
+    invalid.0[0][0] = 1;
+    invalid.0[0][8] = 1;
+    assert_eq!(DlxSolver::from(invalid).solution_cnt(), 0);
+    assert!(DlxSolver::from(invalid).any_solution().is_none());
+
+    // 1 ~ 9 以外的数
+    for num in [10, 127, -1] {
+        let mut invalid = Grid([[0; 9]; 9]);
+        invalid.0[4][4] = num;
+        assert_eq!(DlxSolver::from(invalid).solution_cnt(), 0);
+    }
+}
+
+#[test]
+fn bitboard_solver() {
+    for i in 0..50 {
+        let puzzle = random_sudoku_puzzle_ultimate();
+        let mut solver = BitboardSolver::from(puzzle);
+        assert!(solver.have_unique_solution());
+        assert!(solver.difficulty() > 0.0);
+        let solution = solver.any_solution().unwrap();
+        assert!(judge_sudoku(&solution).1);
+        assert_eq!(solution, DlxSolver::from(puzzle).any_solution().unwrap());
+
+        // 挖去若干数后解的个数应与 DlxSolver 一致
+        let mut sparse = puzzle;
+        for cell in sparse
+            .0
+            .iter_mut()
+            .flatten()
+            .filter(|v| **v > 0)
+            .take(i % 3)
+        {
+            *cell = 0;
+        }
+        assert_eq!(
+            BitboardSolver::from(sparse).solution_cnt(),
+            DlxSolver::from(sparse).solution_cnt()
+        );
+    }
+
+    let mut invalid = Grid([[0; 9]; 9]);
+    invalid.0[0][0] = 1;
+    invalid.0[8][0] = 1;
+    assert_eq!(BitboardSolver::from(invalid).solution_cnt(), 0);
+    assert!(BitboardSolver::from(invalid).any_solution().is_none());
+
+    // 作为生成器的 S2 使用
+    let puzzle = random_sudoku_puzzle::<StochasticSolver, BitboardSolver, f32>(55, 0.0, f32::MAX);
+    assert_eq!(puzzle.0.iter().flatten().filter(|v| **v == 0).count(), 55);
+    assert!(BitboardSolver::from(puzzle).have_unique_solution());
+}
+
+#[test]
+fn sat_solver() {
+    for i in 0..10 {
+        let puzzle = random_sudoku_puzzle_ultimate();
+        let mut solver = SatSolver::from(puzzle);
+        assert!(solver.have_unique_solution());
+        let solution = solver.any_solution().unwrap();
+        assert!(judge_sudoku(&solution).1);
+        assert_eq!(solution, DlxSolver::from(puzzle).any_solution().unwrap());
+        assert!(solver.sat_stats().propagations > 0);
+
+        let mut sparse = puzzle;
+        for cell in sparse
+            .0
+            .iter_mut()
+            .flatten()
+            .filter(|v| **v > 0)
+            .take(i % 3)
+        {
+            *cell = 0;
+        }
+        assert_eq!(
+            SatSolver::from(sparse).solution_cnt(),
+            DlxSolver::from(sparse).solution_cnt()
+        );
+
+        // 删去部分候选数后的状态与原题的解相同
+        let mut state = FullState::from(puzzle);
+        for technique in TechniqueId::DEFAULT {
+            if let Some(deduction) = technique.analyze(&state) {
+                for (cells, nums) in deduction.step.eliminations() {
+                    for (r, c) in cells {
+                        for num in nums {
+                            state.remove_candidate_of_cell(*r, *c, *num);
+                        }
+                    }
+                }
+            }
+        }
+        assert!(Cnf::from(&state).clauses().len() >= Cnf::from(puzzle).clauses().len());
+        assert_eq!(
+            SatSolver::from(Cnf::from(&state)).any_solution(),
+            Some(solution)
+        );
+    }
+
+    let cnf = Cnf::from(random_sudoku_puzzle_ultimate());
+    let dimacs = cnf.to_string();
+    let mut lines = dimacs.lines();
+    assert_eq!(
+        lines.next(),
+        Some(format!("p cnf 729 {}", cnf.clauses().len()).as_str())
+    );
+    assert_eq!(lines.count(), cnf.clauses().len());
+
+    // 对角线数独：两条对角线上的数字也互不相同
+    let mut cnf = Cnf::sudoku();
+    cnf.add_house(&(0..9).map(|i| (i, i)).collect::<Vec<_>>());
+    cnf.add_house(&(0..9).map(|i| (i, 8 - i)).collect::<Vec<_>>());
+    let solution = SatSolver::from(cnf).any_solution().unwrap();
+    assert!(judge_sudoku(&solution).1);
+    for i in 0..9 {
+        for j in i + 1..9 {
+            assert_ne!(solution.0[i][i], solution.0[j][j]);
+            assert_ne!(solution.0[i][8 - i], solution.0[j][8 - j]);
+        }
+    }
+
+    let mut invalid = Grid([[0; 9]; 9]);
+    invalid.0[0][0] = 1;
+    invalid.0[1][1] = 1;
+    assert_eq!(SatSolver::from(invalid).solution_cnt(), 0);
+
+    // 1 ~ 9 以外的数
+    for num in [10, 127, -1] {
+        let mut invalid = Grid([[0; 9]; 9]);
+        invalid.0[4][4] = num;
+        assert!(Cnf::from(invalid)
+            .clauses()
+            .iter()
+            .any(|clause| clause.is_empty()));
+        assert_eq!(SatSolver::from(invalid).solution_cnt(), 0);
+    }
+}
+
+#[test]
+fn lazy_solutions() {
+    for i in 0..5 {
+        let puzzle = random_sudoku_puzzle_ultimate();
+        assert_eq!(
+            AdvancedSolver::<FullState>::from(puzzle).solutions_up_to(5),
+            [DlxSolver::from(puzzle).any_solution().unwrap()]
+        );
+        if i >= 3 {
+            continue;
+        }
+
+        // 挖去一个数后通常有上百个解，只取前 50 个
+        let mut sparse = puzzle;
+        for cell in sparse.0.iter_mut().flatten().filter(|v| **v > 0).take(1) {
+            *cell = 0;
+        }
+        let solutions = StochasticSolver::<SimpleState>::from(sparse).solutions_up_to(50);
+        assert_eq!(
+            solutions.len() as u32,
+            DlxSolver::from(sparse).solution_cnt().min(50)
+        );
+        assert!(solutions.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        for solution in &solutions {
+            assert!(judge_sudoku(solution).1);
+            for (v1, v2) in sparse.0.iter().flatten().zip(solution.0.iter().flatten()) {
+                assert!(*v1 == 0 || v1 == v2);
+            }
+        }
+        assert_eq!(
+            AdvancedSolver::<FullState>::from(sparse)
+                .solutions()
+                .take(50)
+                .collect::<Vec<_>>(),
+            solutions
+        );
+    }
+}
+
+#[test]
+fn capped_solution_count() {
+    fn check<S: Solver + From<Grid>>(puzzle: Grid) {
+        // 近乎空白的题目也应立即返回
+        let mut empty = Grid([[0; 9]; 9]);
+        empty.0[0][0] = 1;
+        assert_eq!(
+            S::from(empty).count_solutions(100),
+            SolutionCount::AtLeast(100)
+        );
+        assert!(!S::from(empty).have_unique_solution());
+
+        let mut solver = S::from(puzzle);
+        assert_eq!(solver.count_solutions(2), SolutionCount::Exact(1));
+        // 数解的同时留下了找到的解
+        let solution = solver.last_solution();
+        assert!(solution.is_some());
+        assert_eq!(solver.any_solution(), solution);
+        assert_eq!(solver.count_solutions(1), SolutionCount::AtLeast(1));
+        assert!(solver.have_unique_solution());
+
+        let mut invalid = puzzle;
+        let (r, c) = (0..81)
+            .map(|i| (i / 9, i % 9))
+            .find(|(r, c)| puzzle.0[*r][*c] == 0)
+            .unwrap();
+        invalid.0[r][c] = puzzle.0[r].iter().copied().find(|v| *v > 0).unwrap();
+        assert_eq!(S::from(invalid).count_solutions(2), SolutionCount::Exact(0));
+    }
+
+    let puzzle = random_sudoku_puzzle_ultimate();
+    check::<StochasticSolver<SimpleState>>(puzzle);
+    check::<AdvancedSolver<FullState>>(puzzle);
+    check::<DlxSolver>(puzzle);
+    check::<BitboardSolver>(puzzle);
+    check::<SatSolver>(puzzle);
+}
+
+#[test]
+fn time_budget() {
+    // 空盘的解数极多，只能被预算打断
+    let empty = Grid([[0; 9]; 9]);
+    let budget = Budget::default().timeout(Duration::from_millis(50));
+    assert_eq!(
+        StochasticSolver::<SimpleState>::from(empty).try_count_solutions(u64::MAX, &budget),
+        Err(Interrupted::Timeout)
+    );
+    assert_eq!(
+        AdvancedSolver::<FullState>::from(empty).try_count_solutions(u64::MAX, &budget),
+        Err(Interrupted::Timeout)
+    );
+    assert_eq!(
+        BitboardSolver::from(empty).try_count_solutions(u64::MAX, &budget),
+        Err(Interrupted::Timeout)
+    );
+
+    let token = CancellationToken::new();
+    let budget = Budget::default().token(token.clone());
+    let puzzle = random_sudoku_puzzle_ultimate();
+    let mut solver = AdvancedSolver::<FullState>::from(puzzle);
+    let solution = solver.try_any_solution(&budget).unwrap().unwrap();
+    assert!(judge_sudoku(&solution).1);
+    assert_eq!(
+        StochasticSolver::<SimpleState>::from(puzzle).try_count_solutions(2, &budget),
+        Ok(SolutionCount::Exact(1))
+    );
+    token.cancel();
+    assert_eq!(
+        solver.try_any_solution(&budget),
+        Err(Interrupted::Cancelled)
+    );
+    // 不带预算的方法不受影响
+    assert_eq!(solver.any_solution(), Some(solution));
+
+    // 不可能满足的难度范围
+    let budget = Budget::default().timeout(Duration::from_millis(200));
+    assert_eq!(
+        try_random_sudoku_puzzle::<StochasticSolver, AdvancedSolver, f32>(
+            30,
+            f32::MAX,
+            f32::MAX,
+            &budget
+        ),
+        Err(Interrupted::Timeout)
+    );
+
+    // 生成器同样会被预算打断
+    let config = GenConfig::default().budget(Budget::default().timeout(Duration::from_millis(10)));
+    assert_eq!(
+        generate(Difficulty::ExtraHard, &config),
+        Err(Interrupted::Timeout)
+    );
+}
+
+#[test]
+fn seeded_generation() {
+    let empty = Grid([[0; 9]; 9]);
+    let grid = StochasticSolver::<SimpleState>::from(empty)
+        .with_seed(1)
+        .any_solution();
+    assert!(judge_sudoku(&grid.unwrap()).1);
+    assert_eq!(
+        StochasticSolver::<SimpleState>::from(empty)
+            .with_seed(1)
+            .any_solution(),
+        grid
+    );
+    assert_ne!(
+        StochasticSolver::<SimpleState>::from(empty)
+            .with_seed(2)
+            .any_solution(),
+        grid
+    );
+
+    for seed in 0..3 {
+        let puzzle = random_sudoku_puzzle_easy_with_rng(&mut StdRng::seed_from_u64(seed));
+        assert_eq!(
+            random_sudoku_puzzle_easy_with_rng(&mut StdRng::seed_from_u64(seed)),
+            puzzle
+        );
+        assert!(BitboardSolver::from(puzzle).have_unique_solution());
+    }
+    assert_eq!(
+        random_sudoku_puzzle_normal_with_rng(&mut StdRng::seed_from_u64(20261019)),
+        random_sudoku_puzzle_normal_with_rng(&mut StdRng::seed_from_u64(20261019))
+    );
+    assert_eq!(
+        random_sudoku_puzzle_ultimate_with_rng(&mut StdRng::seed_from_u64(7)),
+        random_sudoku_puzzle_ultimate_with_rng(&mut StdRng::seed_from_u64(7))
+    );
+    assert_eq!(
+        random_sudoku_puzzle_with_rng::<StochasticSolver, BitboardSolver, f32>(
+            60,
+            0.0,
+            f32::MAX,
+            &mut StdRng::seed_from_u64(3)
+        ),
+        random_sudoku_puzzle_with_rng::<StochasticSolver, BitboardSolver, f32>(
+            60,
+            0.0,
+            f32::MAX,
+            &mut StdRng::seed_from_u64(3)
+        )
+    );
+}
+
+#[test]
+fn puzzle_validation() {
+    let puzzle = random_sudoku_puzzle_ultimate();
+    assert_eq!(validate(&puzzle), Ok(()));
+    assert!(StochasticSolver::<SimpleState>::new(puzzle).is_ok());
+    assert!(AdvancedSolver::<FullState>::new(puzzle)
+        .unwrap()
+        .have_unique_solution());
+
+    let mut out_of_range = puzzle;
+    out_of_range.0[4][7] = 10;
+    assert_eq!(
+        validate(&out_of_range),
+        Err(PuzzleError::OutOfRange {
+            r: 4,
+            c: 7,
+            value: 10
+        })
+    );
+    out_of_range.0[4][7] = -1;
+    assert!(StochasticSolver::<SimpleState>::new(out_of_range).is_err());
+
+    let mut conflict = Grid([[0; 9]; 9]);
+    conflict.0[0][0] = 5;
+    conflict.0[2][2] = 5;
+    conflict.0[0][8] = 5;
+    assert_eq!(
+        validate(&conflict),
+        Err(PuzzleError::Conflict(vec![(0, 0), (0, 8), (2, 2)]))
+    );
+    assert!(AdvancedSolver::<FullState>::new(conflict).is_err());
+
+    // R1C1 所在的行、列、宫中出现了 1 ~ 9
+    let mut no_candidate = Grid([[0; 9]; 9]);
+    no_candidate.0[0][1..=4].copy_from_slice(&[1, 2, 3, 4]);
+    no_candidate.0[1][1..=2].copy_from_slice(&[5, 6]);
+    no_candidate.0[5][0] = 7;
+    no_candidate.0[6][0] = 8;
+    no_candidate.0[8][0] = 9;
+    assert_eq!(
+        validate(&no_candidate),
+        Err(PuzzleError::NoCandidate { r: 0, c: 0 })
+    );
+    assert!(StochasticSolver::<SimpleState>::new(no_candidate).is_err());
+}
+
+#[test]
+fn solve_stats() {
+    fn check<S: Solver + From<Grid>>(puzzle: Grid) -> SolveStats {
+        let mut solver = S::from(puzzle);
+        solver.have_unique_solution();
+        let stats = solver.stats().unwrap().clone();
+        assert!(stats.nodes > 0);
+        assert!(stats.guesses < stats.nodes);
+        assert!(stats.max_depth as u64 <= stats.nodes);
+        assert!(stats.max_depth <= 81);
+
+        // 每次调用都会重新统计
+        solver.have_unique_solution();
+        assert_eq!(solver.stats().unwrap().nodes, stats.nodes);
+        stats
+    }
+
+    let puzzle = random_sudoku_puzzle_ultimate();
+    let stats = check::<AdvancedSolver<FullState>>(puzzle);
+    assert!(stats.guesses > 0);
+    assert!(stats.technique_counts[&TechniqueId::HiddenSingle] > 0);
+    assert!(stats
+        .technique_counts
+        .keys()
+        .all(|id| TechniqueId::DEFAULT.contains(id)));
+    assert!(check::<BitboardSolver>(puzzle).guesses > 0);
+    assert!(check::<DlxSolver>(puzzle).technique_counts.is_empty());
+    check::<SatSolver>(puzzle);
+
+    // 对角线上的格子各是所在行唯一的空格：技巧填数不算搜索深度，回溯搜索则每个空格一层
+    let easy = diagonal_blanks(puzzle);
+    let mut solver = AdvancedSolver::<FullState>::from(easy).with_config(
+        SolverConfig::default().techniques(&[TechniqueId::HiddenSingle, TechniqueId::NakedSingle]),
+    );
+    solver.any_solution();
+    let stats = solver.stats().unwrap();
+    assert_eq!(stats.max_depth, 0);
+    assert_eq!(stats.technique_counts.values().sum::<u64>(), 9);
+    assert_eq!(stats.guesses, 0);
+    let stats = check::<StochasticSolver<SimpleState>>(easy);
+    assert_eq!((stats.max_depth, stats.guesses), (9, 0));
+}
+
+#[test]
+fn batch_rating() {
+    let mut puzzles: Vec<_> = (0..6).map(|_| random_sudoku_puzzle_normal()).collect();
+    let mut no_solution = Grid([[0; 9]; 9]);
+    // R1C8 和 R1C9 都只剩候选数 9
+    no_solution.0[0][..7].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7]);
+    no_solution.0[3][7] = 8;
+    no_solution.0[6][8] = 8;
+    let mut conflict = puzzles[0];
+    conflict.0[0][0] = 10;
+    let mut multiple = puzzles[1];
+    multiple.0 = [[0; 9]; 9];
+    puzzles.insert(2, no_solution);
+    puzzles.insert(4, conflict);
+    puzzles.push(multiple);
+
+    for threads in [1, 3] {
+        let config = BatchConfig::default().threads(NonZeroUsize::new(threads).unwrap());
+        let results = rate_batch::<AdvancedSolver<FullState>, f32>(puzzles.clone(), &config);
+        assert_eq!(results.len(), puzzles.len());
+        for (puzzle, result) in puzzles.iter().zip(&results) {
+            assert_eq!(result, &rate::<AdvancedSolver<FullState>, f32>(*puzzle));
+        }
+        assert_eq!(results[2], Err(BatchError::NoSolution));
+        assert!(matches!(
+            results[4],
+            Err(BatchError::Invalid(PuzzleError::OutOfRange {
+                r: 0,
+                c: 0,
+                ..
+            }))
+        ));
+        assert!(!results[8].as_ref().unwrap().unique);
+        for i in [0, 1, 3, 5, 6, 7] {
+            let rated = results[i].as_ref().unwrap();
+            assert!(rated.unique);
+            assert!(judge_sudoku(&rated.solution).1);
+        }
+    }
+    assert!(rate_batch::<BitboardSolver, f32>(vec![], &BatchConfig::default()).is_empty());
+}
+
+#[test]
+fn se_rating() {
+    // 对角线上的格子各是所在宫唯一能填某个数的格子
+    let easy = diagonal_blanks(random_sudoku_puzzle_ultimate());
+    let mut rater = SERater::from(easy);
+    let rating = rater.rate().unwrap();
+    assert_eq!((rating.er, rating.ep, rating.ed), (1.2, 1.2, 1.2));
+    assert_eq!(rating.to_string(), "1.2/1.2/1.2");
+    assert_eq!(rater.path().len(), 9);
+
+    for seed in 0..10 {
+        let puzzle = random_sudoku_puzzle_normal_with_rng(&mut StdRng::seed_from_u64(seed));
+        let mut rater = SERater::from(puzzle);
+        let Some(rating) = rater.rate() else {
+            continue;
+        };
+        assert!(rating.ed <= rating.ep && rating.ep <= rating.er);
+        assert_eq!(rating.ed, rater.path()[0].1);
+        assert_eq!(
+            rating.er,
+            rater
+                .path()
+                .iter()
+                .map(|(_, score)| *score)
+                .fold(0.0, f32::max)
+        );
+        assert!(rater
+            .path()
+            .iter()
+            .all(|(id, _)| SE_TECHNIQUES.iter().any(|(se_id, _)| se_id == id)));
+        // 重新评分结果不变
+        assert_eq!(rater.rate(), Some(rating));
+    }
+
+    // 普通难度的题目常常用到 direct 技巧
+    let directs: Vec<SETechnique> = (0..20)
+        .flat_map(|seed| {
+            let puzzle = random_sudoku_puzzle_normal_with_rng(&mut StdRng::seed_from_u64(seed));
+            let mut rater = SERater::from(puzzle);
+            rater.rate();
+            rater.path().to_vec()
+        })
+        .filter_map(|(technique, _)| match technique {
+            SETechnique::Direct(_) => Some(technique),
+            SETechnique::Basic(_) => None,
+        })
+        .collect();
+    assert!(directs.contains(&SETechnique::Direct(TechniqueId::HiddenPair)));
+    assert_eq!(
+        SETechnique::Direct(TechniqueId::Pointing).to_string(),
+        "Direct Pointing"
+    );
+
+    let mut conflict = Grid([[0; 9]; 9]);
+    conflict.0[0][..7].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7]);
+    conflict.0[3][7] = 8;
+    conflict.0[6][8] = 8;
+    assert_eq!(SERater::from(conflict).rate(), None);
+}
+
+#[test]
+fn difficulty_report() {
+    let puzzle = random_sudoku_puzzle_ultimate();
+    // 默认不记录路径，也就没有报告
+    let mut solver = AdvancedSolver::<FullState>::from(puzzle);
+    solver.any_solution().unwrap();
+    assert!(solver.path().is_empty() && solver.report().is_none());
+
+    let mut solver = AdvancedSolver::<FullState>::from(puzzle)
+        .with_config(SolverConfig::default().record_path(true));
+    assert!(solver.report().is_none());
+    solver.any_solution().unwrap();
+    let report = solver.report().unwrap();
+    assert_eq!(report.score, solver.difficulty());
+    assert_eq!(
+        report.clue_cnt,
+        puzzle.0.iter().flatten().filter(|v| **v > 0).count()
+    );
+    assert_eq!(report.branching.len(), solver.path().len());
+    let guess_steps = solver
+        .path()
+        .iter()
+        .filter(|step| step.technique.is_none())
+        .count();
+    assert_eq!(
+        report.steps_by_category.values().sum::<u64>() as usize + guess_steps,
+        solver.path().len()
+    );
+    assert!(report.guesses as usize <= guess_steps);
+    assert!(report.guesses <= solver.stats().unwrap().guesses);
+    // 猜测的那一步没有技巧可用
+    for (step, width) in solver.path().iter().zip(&report.branching) {
+        assert_eq!(step.technique.is_none(), *width == 0);
+    }
+
+    let easy = diagonal_blanks(puzzle);
+    let mut solver = AdvancedSolver::<FullState>::from(easy)
+        .with_config(SolverConfig::default().record_path(true));
+    solver.any_solution();
+    let report = solver.report().unwrap();
+    assert_eq!(report.hardest, Some(TechniqueId::HiddenSingle));
+    assert_eq!(
+        report.steps_by_category,
+        BTreeMap::from([(TechniqueCategory::Single, 9)])
+    );
+    assert_eq!((report.guesses, report.clue_cnt), (0, 72));
+    // 第 i 步时还剩 9 - i 个空格可填
+    assert_eq!(report.branching, (1..=9).rev().collect::<Vec<_>>());
+}
+
+#[test]
+fn difficulty_tiers() {
+    for pair in Difficulty::ALL.windows(2) {
+        assert_eq!(pair[0].score_range().end, pair[1].score_range().start);
+        assert_eq!(Difficulty::from_score(pair[1].score_range().start), pair[1]);
+        assert_eq!(
+            Difficulty::from_score(pair[0].score_range().end.next_down()),
+            pair[0]
+        );
+    }
+    assert_eq!(Difficulty::from_score(1e9), Difficulty::Ultimate);
+
+    for difficulty in [Difficulty::ExtraEasy, Difficulty::Easy, Difficulty::Normal] {
+        let config = GenConfig::default().seed(20261019);
+        let puzzle = generate(difficulty, &config).unwrap();
+        assert_eq!(generate(difficulty, &config), Ok(puzzle));
+        assert_eq!(classify(&puzzle), difficulty);
+        assert!(BitboardSolver::from(puzzle).have_unique_solution());
+    }
+    assert_eq!(classify(&random_sudoku_puzzle_easy()), Difficulty::Easy);
+    assert_eq!(
+        classify(&random_sudoku_puzzle_ultimate()),
+        Difficulty::Ultimate
+    );
+
+    let config = GenConfig::default().budget(Budget::default().timeout(Duration::ZERO));
+    assert_eq!(
+        generate(Difficulty::Hard, &config),
+        Err(Interrupted::Timeout)
+    );
+}
+
+#[test]
+fn solve_time_prediction() {
+    let puzzles: Vec<_> = (0..12)
+        .map(|i| match i % 2 {
+            0 => random_sudoku_puzzle_easy(),
+            _ => random_sudoku_puzzle_normal(),
+        })
+        .collect();
+    let model = SolveTimeModel::default();
+    for puzzle in &puzzles {
+        let x = features(puzzle).unwrap();
+        assert_eq!(x.len(), feature_names().len());
+        assert_eq!(Some(predict_solve_time(puzzle)), model.predict(puzzle));
+    }
+
+    // 用已知模型生成的数据能拟合回同样的预测
+    let samples: Vec<_> = puzzles
+        .iter()
+        .map(|puzzle| (*puzzle, model.predict(puzzle).unwrap().as_secs_f64()))
+        .collect();
+    let fitted = SolveTimeModel::fit(&samples);
+    for (puzzle, seconds) in &samples {
+        assert!(
+            (fitted.predict(puzzle).unwrap().as_secs_f64() - seconds).abs() < 0.01 * seconds + 0.1
+        );
+    }
+
+    let path = std::env::temp_dir().join(format!("solve_time_model_{}.txt", random::<u64>()));
+    fitted.save(&path).unwrap();
+    let loaded = SolveTimeModel::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, fitted);
+    assert!("clue_cnt 1.0\nunknown 2.0"
+        .parse::<SolveTimeModel>()
+        .is_err());
+    assert!("clue_cnt x".parse::<SolveTimeModel>().is_err());
+    assert!("clue_cnt inf".parse::<SolveTimeModel>().is_err());
+    assert!("clue_cnt NaN".parse::<SolveTimeModel>().is_err());
+    let only_clues: SolveTimeModel = "clue_cnt 2.0".parse().unwrap();
+    assert_eq!(
+        only_clues.predict(&puzzles[0]).unwrap().as_secs_f64(),
+        2.0 * features(&puzzles[0]).unwrap()[1]
+    );
+    // 太长的预测取 Duration::MAX 而不是 panic
+    let huge: SolveTimeModel = "clue_cnt 1e300".parse().unwrap();
+    assert_eq!(huge.predict(&puzzles[0]), Some(Duration::MAX));
+
+    // R1C1 唯一可填的数已被改到 R1C2，无解
+    let mut conflict = DlxSolver::from(puzzles[0]).any_solution().unwrap();
+    conflict.0[0][1] = conflict.0[0][0];
+    conflict.0[0][0] = 0;
+    assert_eq!(SolveTimeModel::default().predict(&conflict), None);
+    assert_eq!(predict_solve_time(&conflict), Duration::ZERO);
+}
+
+#[test]
+fn backdoor_analysis() {
+    let singles = [TechniqueId::HiddenSingle, TechniqueId::NakedSingle];
+    for _ in 0..5 {
+        let puzzle = random_sudoku_puzzle_extraeasy();
+        assert!(solves_with(&puzzle, &[TechniqueId::HiddenSingle]));
+        assert_eq!(backdoor_size(&puzzle), Some((0, vec![])));
+    }
+
+    for puzzle in [
+        random_sudoku_puzzle_normal(),
+        random_sudoku_puzzle_ultimate(),
+    ] {
+        let (size, cells) = backdoor_size(&puzzle).unwrap();
+        assert_eq!(size, cells.len());
+        assert_eq!(size == 0, solves_with(&puzzle, &singles));
+        let solution = BitboardSolver::from(puzzle).any_solution().unwrap();
+        let mut revealed = puzzle;
+        for (r, c) in &cells {
+            assert_eq!(puzzle.0[*r][*c], 0);
+            revealed.0[*r][*c] = solution.0[*r][*c];
+        }
+        assert!(solves_with(&revealed, &singles));
+        // 少揭示任何一个格子都不行
+        for (r, c) in &cells {
+            let mut fewer = revealed;
+            fewer.0[*r][*c] = 0;
+            assert!(!solves_with(&fewer, &singles));
+        }
+    }
+
+    let empty = Grid([[0; 9]; 9]);
+    assert_eq!(backdoor_size(&empty), None);
+    assert!(!solves_with(&empty, &TechniqueId::DEFAULT));
+    let mut conflict = BitboardSolver::from(empty).any_solution().unwrap();
+    conflict.0[0][0] = conflict.0[0][1];
+    assert!(!solves_with(&conflict, &singles));
+}
+
+#[test]
+fn technique_requiring_generation() {
+    for (i, technique) in [TechniqueId::Pointing, TechniqueId::HiddenPair]
+        .into_iter()
+        .enumerate()
+    {
+        let config = GenConfig::default().seed(i as u64);
+        let puzzle = generate_requiring(technique, &config).unwrap();
+        assert_eq!(generate_requiring(technique, &config), Ok(puzzle));
+        assert!(BitboardSolver::from(puzzle).have_unique_solution());
+        let j = TechniqueId::DEFAULT
+            .iter()
+            .position(|id| *id == technique)
+            .unwrap();
+        assert!(solves_with(&puzzle, &TechniqueId::DEFAULT[..=j]));
+        assert!(!solves_with(&puzzle, &TechniqueId::DEFAULT[..j]));
+    }
+
+    // 变体按所属的默认技巧生成
+    for technique in TechniqueId::ALL {
+        assert!(TechniqueId::DEFAULT.contains(&technique.family()));
+    }
+    assert_eq!(TechniqueId::NakedPairRow.family(), TechniqueId::NakedPair);
+    let config = GenConfig::default().seed(7);
+    assert_eq!(
+        generate_requiring(TechniqueId::HiddenSingleBlock, &config),
+        generate_requiring(TechniqueId::HiddenSingle, &config)
+    );
+
+    let config = GenConfig::default().budget(Budget::default().timeout(Duration::ZERO));
+    assert_eq!(
+        generate_requiring(TechniqueId::Jellyfish, &config),
+        Err(Interrupted::Timeout)
+    );
+}
+
+#[test]
+fn symmetric_generation() {
+    let symmetries = [
+        Symmetry::None,
+        Symmetry::Rotational180,
+        Symmetry::Rotational90,
+        Symmetry::Mirror,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ];
+    for symmetry in symmetries {
+        // 同一组里每个格子的对称组都相同
+        for i in 0..81 {
+            let orbit = symmetry.orbit(i / 9, i % 9);
+            assert!(orbit.contains(&(i / 9, i % 9)));
+            for (r, c) in &orbit {
+                assert_eq!(symmetry.orbit(*r, *c), orbit);
+            }
+        }
+
+        let config = GenConfig::default().seed(7).symmetry(symmetry);
+        let puzzle = generate(Difficulty::Easy, &config).unwrap();
+        assert!(symmetry.is_symmetric(&puzzle));
+        assert!(BitboardSolver::from(puzzle).have_unique_solution());
+        assert_eq!(classify(&puzzle), Difficulty::Easy);
+    }
+    assert_eq!(Symmetry::Rotational90.orbit(4, 4), vec![(4, 4)]);
+    assert_eq!(
+        Symmetry::Rotational90.orbit(0, 1),
+        vec![(0, 1), (1, 8), (7, 0), (8, 7)]
+    );
+    assert_eq!(Symmetry::Diagonal.orbit(2, 2), vec![(2, 2)]);
+
+    let config = GenConfig::default().symmetry(Symmetry::Rotational180);
+    let puzzle = generate_requiring(TechniqueId::Pointing, &config).unwrap();
+    assert!(Symmetry::Rotational180.is_symmetric(&puzzle));
+    let mut one_clue = Grid([[0; 9]; 9]);
+    one_clue.0[0][1] = 1;
+    assert!(!Symmetry::Rotational180.is_symmetric(&one_clue));
+    assert!(Symmetry::None.is_symmetric(&one_clue));
+}
+
+#[test]
+fn mask_generation() {
+    let shape = random_sudoku_puzzle_easy();
+    let mask = shape.0.map(|row| row.map(|num| num > 0));
+    let config = GenConfig::default().seed(5);
+    let puzzle = generate_with_mask(mask, &config).unwrap();
+    assert_eq!(generate_with_mask(mask, &config), Ok(puzzle));
+    assert_eq!(puzzle.0.map(|row| row.map(|num| num > 0)), mask);
+    assert!(BitboardSolver::from(puzzle).have_unique_solution());
+
+    // 手画的 X 形遮罩，不取自任何生成的题目
+    let x_shape: [[bool; 9]; 9] = std::array::from_fn(|r| {
+        std::array::from_fn(|c| {
+            (r == c || r + c == 8 || (r == 4 && c % 2 == 0) || (c == 4 && r % 2 == 0))
+                && (r, c) != (4, 4)
+        })
+    });
+    assert_eq!(x_shape.iter().flatten().filter(|keep| **keep).count(), 24);
+    for seed in 0..3 {
+        let puzzle = generate_with_mask(x_shape, &GenConfig::default().seed(seed)).unwrap();
+        assert_eq!(puzzle.0.map(|row| row.map(|num| num > 0)), x_shape);
+        assert!(BitboardSolver::from(puzzle).have_unique_solution());
+    }
+
+    // 少于 17 个线索的题目不可能有唯一解
+    let mut sparse = [[false; 9]; 9];
+    for (i, row) in sparse.iter_mut().enumerate() {
+        row[i] = true;
+    }
+    assert_eq!(
+        generate_with_mask(sparse, &GenConfig::default()),
+        Err(MaskError::TooFewClues(9))
+    );
+}
+
+#[test]
+fn minimal_puzzles() {
+    for _ in 0..5 {
+        let puzzle = random_sudoku_puzzle_easy();
+        let solution = BitboardSolver::from(puzzle).any_solution().unwrap();
+        let redundant = redundant_clues(&puzzle);
+        assert_eq!(redundant.is_empty(), is_minimal(&puzzle));
+        for (r, c) in &redundant {
+            let mut fewer = puzzle;
+            fewer.0[*r][*c] = 0;
+            assert!(BitboardSolver::from(fewer).have_unique_solution());
+        }
+
+        let minimal = make_minimal(&puzzle);
+        assert!(is_minimal(&minimal));
+        assert!(redundant_clues(&minimal).is_empty());
+        assert_eq!(BitboardSolver::from(minimal).any_solution(), Some(solution));
+        for i in 0..81 {
+            let (r, c) = (i / 9, i % 9);
+            assert!(minimal.0[r][c] == 0 || minimal.0[r][c] == puzzle.0[r][c]);
+        }
+        assert_eq!(make_minimal(&minimal), minimal);
+    }
+
+    // 给最小题目加回一个线索，这个线索一定多余
+    let minimal = make_minimal(&random_sudoku_puzzle_normal());
+    let solution = BitboardSolver::from(minimal).any_solution().unwrap();
+    let (r, c) = (0..81)
+        .map(|i| (i / 9, i % 9))
+        .find(|(r, c)| minimal.0[*r][*c] == 0)
+        .unwrap();
+    let mut extra = minimal;
+    extra.0[r][c] = solution.0[r][c];
+    assert!(!is_minimal(&extra));
+    assert!(redundant_clues(&extra).contains(&(r, c)));
+
+    let empty = Grid([[0; 9]; 9]);
+    assert!(!is_minimal(&empty));
+    assert!(redundant_clues(&empty).is_empty());
+    assert_eq!(make_minimal(&empty), empty);
+}
+
+#[test]
+fn advanced_solver_config() {
+    for _ in 0..10 {
+        let puzzle = random_sudoku_puzzle_ultimate();
+        let mut default_solver = AdvancedSolver::<FullState>::from(puzzle);
+        let mut singles_solver = AdvancedSolver::<FullState>::from(puzzle).with_config(
+            SolverConfig::default()
+                .techniques(&[TechniqueId::NakedSingle, TechniqueId::HiddenSingle])
+                .score(TechniqueId::HiddenSingle, 1.0)
+                .guess_score(20.0),
+        );
+        assert!(default_solver.have_unique_solution());
+        assert!(singles_solver.have_unique_solution());
+        assert!(judge_sudoku(&singles_solver.any_solution().unwrap()).1);
+        assert!(singles_solver.difficulty() > default_solver.difficulty());
+    }
+}
+
+#[test]
+fn easiest_step_policy() {
+    let mut reversed = TechniqueId::DEFAULT;
+    reversed.reverse();
+    for _ in 0..5 {
+        let puzzle = random_sudoku_puzzle_normal();
+        let mut solvers = [TechniqueId::DEFAULT, reversed].map(|techniques| {
+            AdvancedSolver::<FullState>::from(puzzle).with_config(
+                SolverConfig::default()
+                    .techniques(&techniques)
+                    .policy(StepPolicy::Easiest)
+                    .record_path(true),
+            )
+        });
+        for solver in &mut solvers {
+            assert!(solver.have_unique_solution());
+        }
+        // 与技巧的顺序无关
+        assert_eq!(solvers[0].difficulty(), solvers[1].difficulty());
+        assert_eq!(
+            solvers[0].stats().unwrap().technique_counts,
+            solvers[1].stats().unwrap().technique_counts
+        );
+
+        // 每一步都不比同一盘面上其他可用的步骤难
+        let mut state = FullState::from(puzzle);
+        for step in solvers[0].path() {
+            let easiest = TechniqueId::DEFAULT
+                .iter()
+                .flat_map(|id| id.analyze_all(&state))
+                .map(|deduction| deduction.score)
+                .fold(f32::MAX, f32::min);
+            if step.technique.is_some() {
+                assert_eq!(step.score, easiest);
+            }
+            step.step.apply(&mut state);
+        }
+    }
+}
+
+#[test]
+fn progressive_hint() {
+    for _ in 0..20 {
+        let puzzle = random_sudoku_puzzle_ultimate();
+        let solution = AdvancedSolver::<FullState>::from(puzzle)
+            .any_solution()
+            .unwrap();
+        let state = FullState::from(puzzle);
+
+        let Some(hint1) = hint(&state, HintLevel::Technique) else {
+            assert!(hint(&state, HintLevel::Step).is_none());
+            continue;
+        };
+        assert!(hint1.houses.is_none() && hint1.cells.is_none() && hint1.step.is_none());
+
+        let hint4 = hint(&state, HintLevel::Step).unwrap();
+        assert_eq!(hint1.technique, hint4.technique);
+        assert!(!hint4.houses.unwrap().is_empty());
+        assert!(!hint4.cells.unwrap().is_empty());
+        let step = hint4.step.unwrap();
+        for DirectOption(r, c, num) in step.placements() {
+            assert_eq!(solution.0[*r][*c], *num);
+        }
+        for (cells, nums) in step.eliminations() {
+            for (r, c) in cells {
+                assert!(!nums.contains(&solution.0[*r][*c]));
+            }
+        }
+    }
+}
+
+#[test]
+fn technique_explanation() {
+    let info = TechniqueInfo::Pointing(PointingInfo {
+        block: 3,
+        rem_house: House::Row(4),
+        rem_num: 7,
+        rem_cells: vec![(4, 0), (4, 1)],
+    });
+    assert_eq!(
+        explain(&info, &English),
+        "In block 4, the digit 7 can only go in row 5, so 7 can be removed from R5C1 and R5C2."
+    );
+    assert_eq!(
+        explain(&info, &Chinese),
+        "在第4宫中，数字7只能出现在第5行，所以可以从R5C1和R5C2中删去候选数7。"
+    );
+
+    for _ in 0..20 {
+        let state = FullState::from(random_sudoku_puzzle_ultimate());
+        for technique in TechniqueId::DEFAULT {
+            if let Some(deduction) = technique.analyze(&state) {
+                assert!(explain(&deduction.info, &English).ends_with('.'));
+                assert!(explain(&deduction.info, &Chinese).ends_with('。'));
+            }
+        }
+    }
+}
+
+#[test]
+fn techniques_soundness() {
+    let mut corpus: Vec<Grid> = (0..10)
+        .map(|_| random_sudoku_puzzle::<StochasticSolver, AdvancedSolver, f32>(55, 0.0, f32::MAX))
+        .collect();
+    corpus.extend((0..10).map(|_| random_sudoku_puzzle_ultimate()));
+    for puzzle in corpus {
+        let solution = AdvancedSolver::<FullState>::from(puzzle)
+            .any_solution()
+            .unwrap();
+        if let Err(violation) = verify_solving_path(&puzzle, &solution, &TechniqueId::ALL) {
+            panic!("{}", violation);
+        }
+
+        // analyze_all 的第一步就是 analyze 找到的那一步，其余每一步也都与解相符
+        let state = FullState::from(puzzle);
+        for technique in TechniqueId::ALL {
+            let deductions = technique.analyze_all(&state);
+            assert_eq!(
+                deductions.first().map(|d| d.step.clone()),
+                technique.analyze(&state).map(|d| d.step)
+            );
+            for deduction in deductions {
+                for DirectOption(r, c, num) in deduction.step.placements() {
+                    assert_eq!(solution.0[*r][*c], *num);
+                }
+                for (cells, nums) in deduction.step.eliminations() {
+                    assert!(cells
+                        .iter()
+                        .all(|(r, c)| !nums.contains(&solution.0[*r][*c])));
+                }
+            }
+        }
+    }
+}
+
 #[test]
 fn overlap_region_test() {
     // row and columns
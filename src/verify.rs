@@ -0,0 +1,132 @@
+use std::fmt::Display;
+
+use crate::{
+    state::{
+        full_state::FullState, CandidatesSettable, Fillable, State, TrackingCandidateCountOfCell,
+        TrackingCandidates, TrackingCellCountOfCandidate,
+    },
+    techniques::{Deduction, DirectOption, Step, TechniqueId, TechniqueInfo},
+    Grid,
+};
+
+/// A step which contradicts the known solution of the puzzle.
+#[derive(Clone, Debug)]
+pub struct Violation {
+    pub technique: TechniqueId,
+    pub info: TechniqueInfo,
+    pub step: Step,
+    // 出错时的盘面
+    pub grid: Grid,
+    // 与解矛盾的填数
+    pub wrong_placements: Vec<DirectOption>,
+    // 删去了解中数字的候选数 (r, c, num)
+    pub wrong_eliminations: Vec<(usize, usize, i8)>,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} is unsound: {}", self.technique, self.step)?;
+        for DirectOption(r, c, num) in &self.wrong_placements {
+            writeln!(f, "R{}C{} filled with {}", r + 1, c + 1, num)?;
+        }
+        for (r, c, num) in &self.wrong_eliminations {
+            writeln!(f, "{} removed from R{}C{}", num, r + 1, c + 1)?;
+        }
+        writeln!(f, "{:?}", self.info)?;
+        write!(f, "{}", self.grid)
+    }
+}
+
+/// Check that `deduction`, found on a state whose grid is `grid`,
+/// neither fills a cell with a wrong digit nor removes the right one.
+pub fn verify_deduction(
+    deduction: &Deduction,
+    grid: &Grid,
+    solution: &Grid,
+) -> Result<(), Box<Violation>> {
+    let wrong_placements: Vec<DirectOption> = deduction
+        .step
+        .placements()
+        .iter()
+        .filter(|DirectOption(r, c, num)| solution.0[*r][*c] != *num)
+        .cloned()
+        .collect();
+    let wrong_eliminations: Vec<(usize, usize, i8)> = deduction
+        .step
+        .eliminations()
+        .iter()
+        .flat_map(|(cells, nums)| {
+            cells
+                .iter()
+                .filter(|(r, c)| nums.contains(&solution.0[*r][*c]))
+                .map(|(r, c)| (*r, *c, solution.0[*r][*c]))
+        })
+        .collect();
+    if wrong_placements.is_empty() && wrong_eliminations.is_empty() {
+        return Ok(());
+    }
+    Err(Box::new(Violation {
+        technique: deduction.technique,
+        info: deduction.info.clone(),
+        step: deduction.step.clone(),
+        grid: *grid,
+        wrong_placements,
+        wrong_eliminations,
+    }))
+}
+
+/// Run `technique` on `state` and check its result against `solution`.
+pub fn verify<T>(technique: TechniqueId, state: &T, solution: &Grid) -> Result<(), Box<Violation>>
+where
+    T: State + TrackingCandidates + TrackingCandidateCountOfCell + TrackingCellCountOfCandidate,
+{
+    match technique.analyze(state) {
+        Some(deduction) => verify_deduction(&deduction, &state.grid(), solution),
+        None => Ok(()),
+    }
+}
+
+/// Walk through a whole solving path of `puzzle`, checking every one of `techniques`
+/// at every state on the way. The path applies the first technique which makes progress,
+/// and reveals a cell from `solution` when none of them do.
+pub fn verify_solving_path(
+    puzzle: &Grid,
+    solution: &Grid,
+    techniques: &[TechniqueId],
+) -> Result<(), Box<Violation>> {
+    let mut state = FullState::from(*puzzle);
+    loop {
+        let mut next = None;
+        for technique in techniques {
+            if let Some(deduction) = technique.analyze(&state) {
+                verify_deduction(&deduction, &state.grid(), solution)?;
+                next.get_or_insert(deduction.step);
+            }
+        }
+
+        match next {
+            Some(step) => {
+                for (cells, nums) in step.eliminations() {
+                    for (r, c) in cells {
+                        for num in nums {
+                            state.remove_candidate_of_cell(*r, *c, *num);
+                        }
+                    }
+                }
+                for DirectOption(r, c, num) in step.placements() {
+                    state.fill_cell(*r, *c, *num);
+                }
+            }
+            None => {
+                let Some((r, c)) = (0..81)
+                    .map(|i| (i / 9, i % 9))
+                    .filter(|(r, c)| state.is_cell_empty(*r, *c))
+                    .min_by_key(|(r, c)| state.candidate_cnt_of_cell(*r, *c))
+                else {
+                    return Ok(());
+                };
+                state.fill_cell(r, c, solution.0[r][c]);
+            }
+        }
+    }
+}